@@ -1,9 +1,15 @@
 use std::path::PathBuf;
 
 use anyhow::Context;
-use tokio::io::AsyncWriteExt;
+use tokio::{io::AsyncWriteExt, sync::mpsc};
 
-use crate::{app::App, cache_codec::CacheCodecApp, config::Config, git_provider::Repository};
+use crate::{
+    app::App,
+    cache_codec::CacheCodecApp,
+    config::Config,
+    git_provider::{Repository, VecRepositoryExt},
+    interactive::ItemBatch,
+};
 
 pub struct Cache {
     app: &'static App,
@@ -29,18 +35,35 @@ impl Cache {
             .cache_codec()
             .serialize_repositories(repositories)?;
 
-        let mut cache_file = tokio::fs::File::create(location)
+        // Write aside and rename, so a refresh cut short by exiting the
+        // picker never leaves a truncated cache behind.
+        let temp_location = location.with_extension("proto.tmp");
+        let mut cache_file = tokio::fs::File::create(&temp_location)
             .await
             .context("failed to create cache file")?;
         cache_file
             .write_all(&cache_content)
             .await
             .context("failed to write cache content to file")?;
+        tokio::fs::rename(&temp_location, &location)
+            .await
+            .context("failed to replace cache file")?;
 
         Ok(())
     }
 
     pub async fn get(&self) -> anyhow::Result<Option<Vec<Repository>>> {
+        self.read(true).await
+    }
+
+    /// Returns the cached repositories regardless of whether the cache has
+    /// expired. Used when comparing a fresh listing against the previous one,
+    /// and to look up repositories without fetching them.
+    pub async fn get_stale(&self) -> anyhow::Result<Option<Vec<Repository>>> {
+        self.read(false).await
+    }
+
+    async fn read(&self, check_expiry: bool) -> anyhow::Result<Option<Vec<Repository>>> {
         tracing::debug!("fetching repositories");
 
         let location = self.app.config.get_cache_file_location()?;
@@ -52,7 +75,9 @@ impl Cache {
             return Ok(None);
         }
 
-        if let Some(cache_duration) = self.app.config.settings.cache.duration.get_duration() {
+        if check_expiry
+            && let Some(cache_duration) = self.app.config.settings.cache.duration.get_duration()
+        {
             let metadata = tokio::fs::metadata(&location).await?;
 
             if let Ok(file_modified_last) = metadata
@@ -109,10 +134,8 @@ impl Cache {
 pub async fn load_repositories(app: &'static App, use_cache: bool) -> anyhow::Result<Vec<Repository>> {
     use crate::projects_list::ProjectsListApp;
 
-    if use_cache {
-        if let Some(repos) = app.cache().get().await? {
-            return Ok(repos);
-        }
+    if use_cache && let Some(repos) = app.cache().get().await? {
+        return Ok(repos);
     }
 
     tracing::info!("fetching repositories...");
@@ -121,6 +144,40 @@ pub async fn load_repositories(app: &'static App, use_cache: bool) -> anyhow::Re
     Ok(repositories)
 }
 
+/// Streams freshly fetched repositories as each provider finishes. The cache
+/// is updated once every provider has succeeded.
+pub fn stream_repositories(app: &'static App) -> mpsc::UnboundedReceiver<ItemBatch<Repository>> {
+    use crate::projects_list::ProjectsListApp;
+
+    let mut batches = app.projects_list().stream_projects();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut repositories = Vec::new();
+        let mut complete = true;
+        while let Some(batch) = batches.recv().await {
+            match &batch.items {
+                Ok(items) => repositories.extend(items.iter().cloned()),
+                Err(e) => {
+                    tracing::warn!("failed to fetch repositories from {}: {e:#}", batch.source);
+                    complete = false;
+                }
+            }
+            // The picker may already be gone; still finish updating the cache.
+            let _ = tx.send(batch);
+        }
+
+        if complete {
+            repositories.collect_unique();
+            if let Err(e) = app.cache().update(&repositories).await {
+                tracing::warn!("failed to update cache: {e:#}");
+            }
+        }
+    });
+
+    rx
+}
+
 pub trait CacheApp {
     fn cache(&self) -> Cache;
 }
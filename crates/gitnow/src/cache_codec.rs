@@ -25,6 +25,13 @@ impl CacheCodec {
                 owner: repo.owner,
                 repo_name: repo.repo_name,
                 ssh_url: repo.ssh_url,
+                id: repo.id,
+                language: repo.language.unwrap_or_default(),
+                topics: repo.topics,
+                archived: repo.archived,
+                description: repo.description.unwrap_or_default(),
+                default_branch: repo.default_branch.unwrap_or_default(),
+                pushed_at: repo.pushed_at.map(|t| t.timestamp()).unwrap_or_default(),
             });
         }
 
@@ -43,6 +50,16 @@ impl CacheCodec {
                 owner: codec_repo.owner,
                 repo_name: codec_repo.repo_name,
                 ssh_url: codec_repo.ssh_url,
+                id: codec_repo.id,
+                language: Some(codec_repo.language).filter(|l| !l.is_empty()),
+                topics: codec_repo.topics,
+                archived: codec_repo.archived,
+                description: Some(codec_repo.description).filter(|d| !d.is_empty()),
+                default_branch: Some(codec_repo.default_branch).filter(|b| !b.is_empty()),
+                pushed_at: match codec_repo.pushed_at {
+                    0 => None,
+                    secs => chrono::DateTime::from_timestamp(secs, 0),
+                },
             });
         }
 
@@ -1,86 +1,181 @@
+pub mod actions;
+pub mod exec;
+pub mod last;
+pub mod migrate_layout;
+pub mod mirrors;
 pub mod project;
+pub mod prune;
+pub mod recent;
 pub mod root;
 pub mod shell;
 pub mod skill;
+pub mod status;
+pub mod sync;
 pub mod update;
 pub mod worktree;
 pub mod clone {
+    use std::path::PathBuf;
 
-    use std::sync::Arc;
-
-    use futures::future::join_all;
     use regex::Regex;
 
     use crate::{
-        app::App, cache::CacheApp, custom_command::CustomCommandApp, git_clone::GitCloneApp,
+        app::App,
+        cache::load_repositories,
+        components::task_list::{TaskList, TaskState},
+        custom_command::CustomCommandApp,
+        git_clone::{find_clone_profile, GitCloneApp},
+        git_provider::Repository,
+        interactive::InteractiveApp,
+        layout::PathLayoutApp,
+        theme::ThemeApp,
     };
 
+    /// Clones running at once.
+    const CLONE_CONCURRENCY: usize = 5;
+
     #[derive(clap::Parser)]
     pub struct CloneCommand {
-        #[arg(long = "search")]
-        search: String,
+        /// Regex matched against `provider/owner/repo`; with --interactive it
+        /// narrows the picker
+        #[arg(long = "search", required_unless_present = "interactive")]
+        search: Option<String>,
+
+        /// Pick the repositories to clone in a multi-select picker
+        #[arg(long = "interactive", short = 'i', default_value = "false")]
+        interactive: bool,
+
+        /// Clone with this entry of settings.clone_profiles
+        #[arg(long = "profile")]
+        profile: Option<String>,
     }
 
     impl CloneCommand {
         pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
-            let repos = app.cache().get().await?.ok_or(anyhow::anyhow!(
-                "failed to get cache, do a gitnow update first"
-            ))?;
+            if let Some(profile) = &self.profile {
+                find_clone_profile(app, profile)?;
+            }
 
-            let search = Regex::new(&self.search)?;
+            let mut repos = load_repositories(app, true).await?;
 
-            let filtered_repos = repos
-                .iter()
-                .filter(|r| search.is_match(&r.to_rel_path().display().to_string()))
-                .collect::<Vec<_>>();
-
-            let concurrency_limit = Arc::new(tokio::sync::Semaphore::new(5));
-            let mut handles = Vec::default();
-            for repo in filtered_repos {
-                let config = app.config.clone();
-                let custom_command = app.custom_command();
-                let git_clone = app.git_clone();
-                let repo = repo.clone();
-                let concurrency = Arc::clone(&concurrency_limit);
-
-                let handle = tokio::spawn(async move {
-                    let permit = concurrency.acquire().await?;
-
-                    let project_path = config.settings.projects.directory.join(repo.to_rel_path());
-                    if !project_path.exists() {
-                        eprintln!("cloning repository: {}", repo.to_rel_path().display());
-                        git_clone.clone_repo(&repo, false).await?;
-
-                        custom_command
-                            .execute_post_clone_command(&project_path)
-                            .await?;
-                    }
+            if let Some(search) = &self.search {
+                let search = Regex::new(search)?;
+                repos.retain(|r| search.is_match(&r.to_rel_path().display().to_string()));
+            }
 
-                    drop(permit);
+            if self.interactive {
+                repos = app.interactive().interactive_multi_search(&repos)?;
+            }
 
-                    Ok::<(), anyhow::Error>(())
-                });
+            let layout = app.layout();
+            let targets = repos
+                .into_iter()
+                .map(|r| {
+                    let path = layout.path(&r);
+                    (r, path)
+                })
+                .collect();
 
-                handles.push(handle);
-            }
+            clone_all(app, targets, self.profile.take()).await
+        }
+    }
+
+    /// Clones each repository into its path with a live progress list,
+    /// skipping paths that already exist, between its pre and post clone
+    /// commands, and prints a summary. Fails if any repository failed to clone,
+    /// or its post clone command failed with `fail_on_error`, which the
+    /// summary lists apart. `profile` overrides the clone profile matching
+    /// each repository.
+    pub async fn clone_all(
+        app: &'static App,
+        targets: Vec<(Repository, PathBuf)>,
+        profile: Option<String>,
+    ) -> anyhow::Result<()> {
+        let (existing, targets): (Vec<_>, Vec<_>) =
+            targets.into_iter().partition(|(_, path)| path.exists());
+        if !existing.is_empty() {
+            eprintln!("skipping {} already cloned", existing.len());
+        }
+        if targets.is_empty() {
+            return Ok(());
+        }
 
-            let res = join_all(handles).await;
+        // Hooks run before the progress list, so their output doesn't
+        // garble it. A failing hook with `fail_on_error` skips the clone.
+        let mut failed = Vec::new();
+        let mut ready = Vec::with_capacity(targets.len());
+        for (repo, path) in targets {
+            match app.custom_command(&repo).execute_pre_clone_command(&path).await {
+                Ok(()) => ready.push((repo, path)),
+                Err(e) => failed.push(format!("{}: {e:#}", repo.to_rel_path().display())),
+            }
+        }
+        let total = ready.len() + failed.len();
+        let targets = ready;
 
-            for res in res {
-                match res {
-                    Ok(Ok(())) => {}
-                    Ok(Err(e)) => {
-                        tracing::error!("failed to clone repo: {}", e);
-                        anyhow::bail!(e)
-                    }
-                    Err(e) => {
-                        tracing::error!("failed to clone repo: {}", e);
-                        anyhow::bail!(e)
+        let states = if targets.is_empty() {
+            Vec::new()
+        } else {
+            let labels = targets
+                .iter()
+                .map(|(r, _)| r.to_rel_path().display().to_string())
+                .collect();
+            TaskList::new(labels)
+                .with_theme(app.theme())
+                .execute(CLONE_CONCURRENCY, |i| {
+                    let git_clone = app.git_clone().with_profile(profile.clone());
+                    let (repo, path) = targets[i].clone();
+                    async move { git_clone.clone_into(&repo, &path).await }
+                })
+                .await?
+        };
+
+        // Repositories that cloned, but whose post clone command failed.
+        let mut hook_failed = Vec::new();
+        for ((repo, path), state) in targets.iter().zip(states) {
+            match state {
+                TaskState::Done => {
+                    // Run after the progress list, so hook output doesn't
+                    // garble it.
+                    if let Err(e) = app.custom_command(repo).execute_post_clone_command(path).await {
+                        hook_failed.push(format!("{}: {e:#}", repo.to_rel_path().display()));
                     }
                 }
+                // Clone errors already name the repository.
+                TaskState::Failed(e) => failed.push(e),
+                TaskState::Cancelled => {
+                    failed.push(format!("{}: cancelled", repo.to_rel_path().display()))
+                }
+                TaskState::Queued | TaskState::Running => {}
             }
+        }
+
+        eprintln!(
+            "cloned {} of {} repositories",
+            total - failed.len(),
+            total
+        );
+        for e in &failed {
+            eprintln!("  ✗ {e}");
+        }
+        if !hook_failed.is_empty() {
+            eprintln!(
+                "post clone command failed for {} repositories",
+                hook_failed.len()
+            );
+            for e in &hook_failed {
+                eprintln!("  ✗ {e}");
+            }
+        }
 
-            Ok(())
+        match (failed.len(), hook_failed.len()) {
+            (0, 0) => Ok(()),
+            (failed, 0) => anyhow::bail!("failed to clone {failed} repositories"),
+            (0, hooks) => {
+                anyhow::bail!("post clone command failed for {hooks} cloned repositories")
+            }
+            (failed, hooks) => anyhow::bail!(
+                "failed to clone {failed} repositories, and post clone command failed for {hooks} cloned repositories"
+            ),
         }
     }
 }
@@ -0,0 +1,329 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Context;
+
+use crate::{
+    app::App, chooser::Chooser, config::Providers, custom_command::CustomCommandApp,
+    git_clone::GitCloneApp,
+    git_provider::Repository, git_repo::GitRepo, history::{HistoryApp, HistoryKind},
+    layout::PathLayoutApp, shell::ShellApp,
+};
+
+use super::{project::add_to_selected_project, worktree::open_worktree};
+
+/// Clipboard tools tried in order by [`RepositoryAction::CopyCloneUrl`].
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Secondary actions offered by the picker's action menu (Tab or Ctrl-O).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepositoryAction {
+    OpenInBrowser,
+    CopyCloneUrl,
+    OpenInEditor,
+    CreateWorktree,
+    AddToProject,
+    Reclone,
+    DeleteClone,
+}
+
+impl RepositoryAction {
+    pub const ALL: [RepositoryAction; 7] = [
+        RepositoryAction::OpenInBrowser,
+        RepositoryAction::CopyCloneUrl,
+        RepositoryAction::OpenInEditor,
+        RepositoryAction::CreateWorktree,
+        RepositoryAction::AddToProject,
+        RepositoryAction::Reclone,
+        RepositoryAction::DeleteClone,
+    ];
+
+    pub fn labels() -> Vec<String> {
+        Self::ALL.iter().map(|a| a.label().to_string()).collect()
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RepositoryAction::OpenInBrowser => "open in browser",
+            RepositoryAction::CopyCloneUrl => "copy clone url",
+            RepositoryAction::OpenInEditor => "open in $EDITOR",
+            RepositoryAction::CreateWorktree => "create worktree",
+            RepositoryAction::AddToProject => "add to project",
+            RepositoryAction::Reclone => "force re-clone",
+            RepositoryAction::DeleteClone => "delete local clone",
+        }
+    }
+
+    pub async fn execute(
+        self,
+        app: &'static App,
+        chooser: &Chooser,
+        repository: &Repository,
+        shell: bool,
+    ) -> anyhow::Result<()> {
+        let project_path = app.layout().path(repository);
+
+        match self {
+            RepositoryAction::OpenInBrowser => {
+                open_in_browser(&web_url(&app.config.providers, repository)).await
+            },
+            RepositoryAction::CopyCloneUrl => copy_to_clipboard(&repository.ssh_url),
+            RepositoryAction::OpenInEditor => {
+                ensure_cloned(app, repository, &project_path).await?;
+                app.history().try_record_repository(repository, &project_path);
+                open_in_editor(&project_path).await
+            }
+            RepositoryAction::CreateWorktree => {
+                open_worktree(app, chooser, repository, None, !shell).await
+            }
+            RepositoryAction::AddToProject => {
+                add_to_selected_project(app, std::slice::from_ref(repository)).await
+            }
+            RepositoryAction::Reclone => {
+                if !reclone(app, repository, &project_path).await? {
+                    return Ok(());
+                }
+                app.history().try_record_repository(repository, &project_path);
+                app.custom_command(repository)
+                    .execute_on_enter_command(&project_path, HistoryKind::Repository)
+                    .await?;
+
+                if shell {
+                    app.shell().spawn_shell(repository).await
+                } else {
+                    chooser.set(&project_path)
+                }
+            }
+            RepositoryAction::DeleteClone => delete_clone(&project_path).await,
+        }
+    }
+}
+
+/// The repository's web page, e.g. `https://github.com/kjuulh/gitnow`. Gitea
+/// serves it under its configured URL without `/api/v1`, keeping any port or
+/// path prefix; other providers at `https://{provider}`.
+fn web_url(providers: &Providers, repository: &Repository) -> String {
+    let base = providers
+        .gitea
+        .iter()
+        .filter_map(|gitea| url::Url::parse(&gitea.url).ok())
+        .find(|url| url.domain() == Some(repository.provider.as_str()))
+        .map(|url| {
+            url.as_str()
+                .trim_end_matches('/')
+                .trim_end_matches("/api/v1")
+                .to_string()
+        })
+        .unwrap_or_else(|| format!("https://{}", repository.provider));
+
+    format!("{base}/{}/{}", repository.owner, repository.repo_name)
+}
+
+async fn ensure_cloned(
+    app: &'static App,
+    repository: &Repository,
+    project_path: &Path,
+) -> anyhow::Result<()> {
+    if project_path.exists() {
+        return Ok(());
+    }
+
+    let hooks = app.custom_command(repository);
+    hooks.execute_pre_clone_command(project_path).await?;
+    eprintln!("cloning {}...", repository.to_rel_path().display());
+    app.git_clone().clone_repo(repository, false).await?;
+    hooks.execute_post_clone_command(project_path).await
+}
+
+/// Replaces the clone at `project_path` with a fresh one, once confirmed.
+/// Returns whether it was re-cloned.
+async fn reclone(
+    app: &'static App,
+    repository: &Repository,
+    project_path: &Path,
+) -> anyhow::Result<bool> {
+    if project_path.exists() && !confirm_discard(project_path, "Re-clone").await? {
+        return Ok(false);
+    }
+
+    let hooks = app.custom_command(repository);
+    hooks.execute_pre_clone_command(project_path).await?;
+    eprintln!("re-cloning {}...", repository.to_rel_path().display());
+    app.git_clone().clone_repo(repository, true).await?;
+    hooks.execute_post_clone_command(project_path).await?;
+
+    Ok(true)
+}
+
+async fn open_in_browser(url: &str) -> anyhow::Result<()> {
+    let opener = match std::env::var("BROWSER") {
+        Ok(browser) if !browser.is_empty() => browser,
+        _ if cfg!(target_os = "macos") => "open".into(),
+        _ => "xdg-open".into(),
+    };
+
+    let status = tokio::process::Command::new(&opener)
+        .arg(url)
+        .status()
+        .await
+        .with_context(|| format!("failed to run {opener}"))?;
+    if !status.success() {
+        anyhow::bail!("{opener} failed to open {url}");
+    }
+
+    Ok(())
+}
+
+fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    for command in CLIPBOARD_COMMANDS {
+        let Ok(mut child) = std::process::Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            eprintln!("copied {text}");
+            return Ok(());
+        }
+    }
+
+    // Nothing to copy with; print it so it can be copied by hand.
+    println!("{text}");
+    Ok(())
+}
+
+async fn open_in_editor(path: &Path) -> anyhow::Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|e| !e.is_empty()))
+        .context("neither VISUAL nor EDITOR is set")?;
+    let mut args = shell_words::split(&editor).context("failed to parse EDITOR")?;
+    if args.is_empty() {
+        anyhow::bail!("EDITOR is empty");
+    }
+    let program = args.remove(0);
+
+    let status = tokio::process::Command::new(&program)
+        .args(args)
+        .arg(path)
+        .current_dir(path)
+        .status()
+        .await
+        .with_context(|| format!("failed to run {program}"))?;
+    if !status.success() {
+        anyhow::bail!("{program} exited with {status}");
+    }
+
+    Ok(())
+}
+
+async fn delete_clone(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        anyhow::bail!("{} is not cloned", path.display());
+    }
+    if !confirm_discard(path, "Delete").await? {
+        return Ok(());
+    }
+
+    tokio::fs::remove_dir_all(path).await?;
+    eprintln!("deleted {}", path.display());
+
+    Ok(())
+}
+
+/// Warns about worktrees, uncommitted changes and unpushed commits in the
+/// clone at `path`, then asks before `action` throws it away.
+async fn confirm_discard(path: &Path, action: &str) -> anyhow::Result<bool> {
+    let bare = path.join(".bare");
+    if bare.exists() {
+        eprintln!("warning: this also deletes every worktree in {}", path.display());
+    }
+    let changes = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["status", "--porcelain"])
+        .output()
+        .await
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).lines().count())
+        .unwrap_or_default();
+    if changes > 0 {
+        eprintln!("warning: {} has {changes} uncommitted changes", path.display());
+    }
+    let repository = if bare.exists() { bare } else { path.to_path_buf() };
+    if let Ok(commits) = GitRepo::new(repository).unpushed_commits().await
+        && commits > 0
+    {
+        eprintln!("warning: {} has {commits} unpushed commits", path.display());
+    }
+
+    eprint!("{action} {}? [y/N] ", path.display());
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    if !input.trim().eq_ignore_ascii_case("y") {
+        eprintln!("aborted");
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Gitea;
+
+    use super::*;
+
+    #[test]
+    fn web_url_points_at_provider_page() {
+        assert_eq!(
+            web_url(&Providers::default(), &Repository {
+                provider: "github.com".into(),
+                owner: "kjuulh".into(),
+                repo_name: "gitnow".into(),
+                ..Default::default()
+            }),
+            "https://github.com/kjuulh/gitnow"
+        );
+    }
+
+    #[test]
+    fn web_url_keeps_gitea_port_and_path_prefix() {
+        let providers = Providers {
+            gitea: vec![Gitea {
+                url: "https://example.com:3000/gitea/api/v1".into(),
+                access_token: None,
+                current_user: None,
+                users: Vec::new(),
+                organisations: Vec::new(),
+                path_template: None,
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            web_url(&providers, &Repository {
+                provider: "example.com".into(),
+                owner: "kjuulh".into(),
+                repo_name: "gitnow".into(),
+                ..Default::default()
+            }),
+            "https://example.com:3000/gitea/kjuulh/gitnow"
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    collections::HashSet,
+    io::Write,
+    process::{ExitStatus, Stdio},
+};
+
+use anyhow::Context;
+use futures::{StreamExt, stream};
+use regex::Regex;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::Command,
+};
+
+use crate::{
+    app::App,
+    cache::load_repositories,
+    commands::clone::clone_all,
+    custom_command::user_shell,
+    fuzzy_matcher::FuzzyMatcherApp,
+    git_provider::Repository,
+    layout::PathLayoutApp,
+    local_clones::{LocalClone, find_local_clones},
+};
+
+/// Locations the command runs in at once, unless --jobs says otherwise.
+const DEFAULT_JOBS: usize = 4;
+
+#[derive(clap::Parser)]
+pub struct ExecCommand {
+    /// Regex matched against each location, e.g. `provider/owner/repo`,
+    /// `provider/owner/repo@worktree` or `project/repo`
+    #[arg(long = "search", required_unless_present = "query")]
+    search: Option<String>,
+
+    /// Search query matched against the repository of each location, with
+    /// qualifiers such as `owner:kjuulh` or `lang:rust`
+    #[arg(long = "query")]
+    query: Option<String>,
+
+    /// Locations the command runs in at once
+    #[arg(long = "jobs", short = 'j', default_value_t = DEFAULT_JOBS)]
+    jobs: usize,
+
+    /// Print each location's output as one block once it finishes, instead
+    /// of prefixing every line with the location
+    #[arg(long = "group", default_value = "false")]
+    group: bool,
+
+    /// Clone matching repositories that aren't cloned yet first
+    #[arg(long = "clone", default_value = "false")]
+    clone: bool,
+
+    /// Clone without listing the repositories and confirming
+    #[arg(long = "force", short = 'f', default_value = "false", requires = "clone")]
+    force: bool,
+
+    /// Skip cache
+    #[arg(long = "no-cache", default_value = "false")]
+    no_cache: bool,
+
+    /// The command to run. A single argument runs as a script with
+    /// `$SHELL -c`, several as a program and its arguments
+    #[arg(last = true, required = true)]
+    command: Vec<String>,
+}
+
+impl ExecCommand {
+    pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        let search = self.search.as_deref().map(Regex::new).transpose()?;
+        let repositories = load_repositories(app, !self.no_cache).await?;
+
+        if self.clone {
+            let layout = app.layout();
+            let missing = missing_repositories(
+                app,
+                &repositories,
+                search.as_ref(),
+                self.query.as_deref(),
+            );
+            if !missing.is_empty() {
+                if !self.force {
+                    eprintln!("Repositories to clone:");
+                    for repository in &missing {
+                        eprintln!("  - {}", rel_path(repository));
+                    }
+                    eprint!("Proceed? [y/N] ");
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                    if !input.trim().eq_ignore_ascii_case("y") {
+                        eprintln!("aborted");
+                        return Ok(());
+                    }
+                }
+
+                let targets = missing
+                    .into_iter()
+                    .map(|repository| (repository.clone(), layout.path(repository)))
+                    .collect();
+                // Run in the repositories that did clone.
+                if let Err(e) = clone_all(app, targets, None).await {
+                    eprintln!("warning: {e:#}");
+                }
+            }
+        }
+
+        let clones = matching_clones(
+            app,
+            find_local_clones(app, &repositories),
+            search.as_ref(),
+            self.query.as_deref(),
+        );
+        if clones.is_empty() {
+            eprintln!("no local clones match");
+            return Ok(());
+        }
+
+        let width = clones
+            .iter()
+            .map(|clone| clone.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        let (args, group) = (&self.command, self.group);
+        let results: Vec<(LocalClone, anyhow::Result<ExitStatus>)> = stream::iter(clones)
+            .map(|clone| async move {
+                let command = command_in(args, &clone);
+                let result = if group {
+                    run_grouped(command, &clone.name).await
+                } else {
+                    run_prefixed(command, &format!("{:<width$}", clone.name)).await
+                };
+                (clone, result)
+            })
+            .buffered(self.jobs.max(1))
+            .collect()
+            .await;
+
+        let failed: Vec<String> = results
+            .iter()
+            .filter_map(|(clone, result)| match result {
+                Ok(status) if status.success() => None,
+                Ok(status) => Some(format!("{}: {status}", clone.name)),
+                Err(e) => Some(format!("{}: {e:#}", clone.name)),
+            })
+            .collect();
+
+        eprintln!(
+            "ran in {} locations, {} failed",
+            results.len(),
+            failed.len()
+        );
+        for e in &failed {
+            eprintln!("  ✗ {e}");
+        }
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "command failed in {} of {} locations",
+                failed.len(),
+                results.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The repositories matching `search` and `query` that aren't cloned yet.
+fn missing_repositories<'a>(
+    app: &'static App,
+    repositories: &'a [Repository],
+    search: Option<&Regex>,
+    query: Option<&str>,
+) -> Vec<&'a Repository> {
+    let layout = app.layout();
+    let mut missing: Vec<&Repository> = match query {
+        Some(query) => app
+            .fuzzy_matcher()
+            .match_items(query, repositories)
+            .into_iter()
+            .map(|(repository, _)| repository)
+            .collect(),
+        None => repositories.iter().collect(),
+    };
+    missing.retain(|repository| {
+        search.is_none_or(|search| search.is_match(&rel_path(repository)))
+            && !layout.path(repository).exists()
+    });
+    missing
+}
+
+/// The `clones` whose location matches `search` and whose repository
+/// matches `query`.
+fn matching_clones(
+    app: &'static App,
+    mut clones: Vec<LocalClone>,
+    search: Option<&Regex>,
+    query: Option<&str>,
+) -> Vec<LocalClone> {
+    if let Some(search) = search {
+        clones.retain(|clone| search.is_match(&clone.name));
+    }
+    if let Some(query) = query {
+        let candidates: Vec<Repository> = clones
+            .iter()
+            .filter_map(|clone| clone.repository.clone())
+            .collect();
+        let matched: HashSet<String> = app
+            .fuzzy_matcher()
+            .match_items(query, &candidates)
+            .into_iter()
+            .map(|(repository, _)| rel_path(repository))
+            .collect();
+        clones.retain(|clone| {
+            clone
+                .repository
+                .as_ref()
+                .is_some_and(|repository| matched.contains(&rel_path(repository)))
+        });
+    }
+    clones
+}
+
+/// A single argument is a script for `$SHELL -c`, several are a program and
+/// its arguments.
+fn command_in(args: &[String], clone: &LocalClone) -> Command {
+    let mut command = match args {
+        [script] => {
+            let mut command = Command::new(user_shell());
+            command.arg("-c").arg(script);
+            command
+        }
+        args => {
+            let mut command = Command::new(&args[0]);
+            command.args(&args[1..]);
+            command
+        }
+    };
+    command
+        .current_dir(&clone.path)
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    command
+}
+
+fn rel_path(repository: &Repository) -> String {
+    repository.to_rel_path().display().to_string()
+}
+
+/// Runs `command`, printing each line of its output as it comes, after
+/// `prefix`.
+async fn run_prefixed(mut command: Command, prefix: &str) -> anyhow::Result<ExitStatus> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run command")?;
+    let stdout = child.stdout.take().context("failed to capture stdout")?;
+    let stderr = child.stderr.take().context("failed to capture stderr")?;
+
+    let (status, (), ()) = tokio::try_join!(
+        child.wait(),
+        print_prefixed(stdout, prefix, false),
+        print_prefixed(stderr, prefix, true),
+    )?;
+
+    Ok(status)
+}
+
+async fn print_prefixed(
+    output: impl AsyncRead + Unpin,
+    prefix: &str,
+    to_stderr: bool,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(output);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line).await? > 0 {
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if to_stderr {
+            eprintln!("{prefix} | {text}");
+        } else {
+            println!("{prefix} | {text}");
+        }
+        line.clear();
+    }
+
+    Ok(())
+}
+
+/// Runs `command` to completion, then prints its output under a header with
+/// `name`, so blocks of different locations never interleave.
+async fn run_grouped(mut command: Command, name: &str) -> anyhow::Result<ExitStatus> {
+    let output = command.output().await.context("failed to run command")?;
+
+    println!("==> {name} <==");
+    std::io::stdout().write_all(&output.stdout)?;
+    std::io::stderr().write_all(&output.stderr)?;
+
+    Ok(output.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::{config::Config, history::HistoryKind};
+
+    fn repository(owner: &str, repo_name: &str) -> Repository {
+        Repository {
+            provider: "github.com".into(),
+            owner: owner.into(),
+            repo_name: repo_name.into(),
+            ..Default::default()
+        }
+    }
+
+    fn clone(name: &str, repository: Option<Repository>) -> LocalClone {
+        LocalClone {
+            kind: HistoryKind::Repository,
+            name: name.into(),
+            path: PathBuf::from("/code").join(name),
+            repository,
+        }
+    }
+
+    async fn app(directory: &Path) -> anyhow::Result<&'static App> {
+        App::new_static(Config::from_string(&format!(
+            r#"
+              [settings]
+              projects = {{ directory = "{}" }}
+            "#,
+            directory.display()
+        ))?)
+        .await
+    }
+
+    #[tokio::test]
+    async fn clones_only_missing_repositories_matching_search_and_query() -> anyhow::Result<()> {
+        let directory = std::env::temp_dir().join(format!("gitnow-exec-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(directory.join("github.com/kjuulh/cloned"))?;
+        let app = app(&directory).await?;
+        let repositories = [
+            repository("kjuulh", "cloned"),
+            repository("kjuulh", "missing"),
+            repository("kjuulh", "other"),
+            repository("someone", "missing"),
+        ];
+        let names = |missing: Vec<&Repository>| -> Vec<String> {
+            missing.into_iter().map(rel_path).collect()
+        };
+
+        let search = Regex::new("/missing$")?;
+        assert_eq!(
+            names(missing_repositories(app, &repositories, Some(&search), None)),
+            ["github.com/kjuulh/missing", "github.com/someone/missing"]
+        );
+        assert_eq!(
+            names(missing_repositories(app, &repositories, None, Some("owner:kjuulh"))),
+            ["github.com/kjuulh/missing", "github.com/kjuulh/other"]
+        );
+        assert_eq!(
+            names(missing_repositories(
+                app,
+                &repositories,
+                Some(&search),
+                Some("owner:kjuulh")
+            )),
+            ["github.com/kjuulh/missing"]
+        );
+
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn selects_clones_matching_search_and_query() -> anyhow::Result<()> {
+        let app = app(Path::new("/code")).await?;
+        let clones = vec![
+            clone("github.com/kjuulh/gitnow", Some(repository("kjuulh", "gitnow"))),
+            clone("github.com/kjuulh/gitnow@main", Some(repository("kjuulh", "gitnow"))),
+            clone("github.com/someone/gitnow", Some(repository("someone", "gitnow"))),
+            clone("demo/scratch", None),
+        ];
+        let names = |clones: Vec<LocalClone>| -> Vec<String> {
+            clones.into_iter().map(|clone| clone.name).collect()
+        };
+
+        let search = Regex::new("gitnow")?;
+        assert_eq!(
+            names(matching_clones(app, clones.clone(), Some(&search), None)),
+            [
+                "github.com/kjuulh/gitnow",
+                "github.com/kjuulh/gitnow@main",
+                "github.com/someone/gitnow"
+            ]
+        );
+        assert_eq!(
+            names(matching_clones(app, clones.clone(), None, Some("owner:kjuulh"))),
+            ["github.com/kjuulh/gitnow", "github.com/kjuulh/gitnow@main"]
+        );
+        let search = Regex::new("@")?;
+        assert_eq!(
+            names(matching_clones(app, clones, Some(&search), Some("owner:kjuulh"))),
+            ["github.com/kjuulh/gitnow@main"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn builds_the_command_in_the_clone() {
+        let clone = clone("github.com/kjuulh/gitnow", None);
+
+        let script = command_in(&["git status | head".into()], &clone);
+        let script = script.as_std();
+        assert_eq!(script.get_program(), user_shell().as_str());
+        assert_eq!(script.get_args().collect::<Vec<_>>(), ["-c", "git status | head"]);
+        assert_eq!(script.get_current_dir(), Some(clone.path.as_path()));
+
+        let program = command_in(&["git".into(), "status".into(), "-sb".into()], &clone);
+        let program = program.as_std();
+        assert_eq!(program.get_program(), "git");
+        assert_eq!(program.get_args().collect::<Vec<_>>(), ["status", "-sb"]);
+        assert_eq!(program.get_current_dir(), Some(clone.path.as_path()));
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{app::App, chooser::Chooser, history::HistoryApp};
+
+use super::recent::open_entry;
+
+#[derive(clap::Parser)]
+pub struct LastCommand {
+    /// Skip spawning a shell in the previous location
+    #[arg(long = "no-shell", default_value = "false")]
+    no_shell: bool,
+}
+
+impl LastCommand {
+    pub async fn execute(&mut self, app: &'static App, chooser: &Chooser) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir().ok();
+
+        // Skip the location we are currently in, so repeated invocations
+        // bounce between the two most recent locations like `cd -`.
+        let entry = app
+            .history()
+            .recent()
+            .into_iter()
+            .filter(|entry| entry.path.exists())
+            .find(|entry| {
+                current_dir
+                    .as_ref()
+                    .is_none_or(|dir| !dir.starts_with(&entry.path))
+            })
+            .ok_or(anyhow::anyhow!("no previous location found in history"))?;
+
+        tracing::debug!("jumping to: {}", entry.path.display());
+
+        open_entry(app, chooser, self.no_shell, &entry).await
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{
+    app::App,
+    cache::load_repositories,
+    git_provider::Repository,
+    layout::{DEFAULT_PATH_TEMPLATE, PathLayout, PathLayoutApp},
+};
+
+#[derive(clap::Parser)]
+pub struct MigrateLayoutCommand {
+    /// Path template of the layout the clones are in now
+    #[arg(long = "from", default_value = DEFAULT_PATH_TEMPLATE)]
+    from: String,
+
+    /// Print the moves without making them
+    #[arg(long = "dry-run", default_value = "false")]
+    dry_run: bool,
+}
+
+/// A clone to move from its path in the old layout to the current one.
+struct Move {
+    from: PathBuf,
+    to: PathBuf,
+}
+
+impl MigrateLayoutCommand {
+    pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        minijinja::Environment::new()
+            .template_from_str(&self.from)
+            .with_context(|| format!("invalid --from template: {}", self.from))?;
+
+        let layout = app.layout();
+        let previous = app.layout().with_template(&self.from);
+        let directory = layout.directory();
+
+        let repositories = load_repositories(app, true).await?;
+        let (moves, conflicts) = plan_moves(&repositories, &previous, &layout);
+
+        if moves.is_empty() && conflicts.is_empty() {
+            eprintln!("all clones already match the layout");
+            return Ok(());
+        }
+
+        let failed = apply_moves(&moves, directory, self.dry_run).await;
+        for to in &conflicts {
+            eprintln!("  skipped {}: already exists", relative(directory, to));
+        }
+
+        if self.dry_run {
+            eprintln!(
+                "would move {} clones, {} skipped",
+                moves.len(),
+                conflicts.len()
+            );
+            return Ok(());
+        }
+
+        eprintln!(
+            "moved {} clones, {} skipped",
+            moves.len() - failed,
+            conflicts.len()
+        );
+        if failed > 0 {
+            anyhow::bail!("failed to move {failed} clones");
+        }
+
+        Ok(())
+    }
+}
+
+/// Pairs each clone in the `previous` layout with its path in `layout`.
+/// Returns the moves to make and the targets skipped because they are taken.
+fn plan_moves(
+    repositories: &[Repository],
+    previous: &PathLayout,
+    layout: &PathLayout,
+) -> (Vec<Move>, Vec<PathBuf>) {
+    let mut moves = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut seen = HashSet::new();
+    for repository in repositories {
+        let from = previous.path(repository);
+        let to = layout.path(repository);
+        // Only move actual clones, never a directory that happens to be
+        // a parent of them in the old layout.
+        if from == to
+            || !(from.join(".git").exists() || from.join(".bare").exists())
+            || !seen.insert(from.clone())
+        {
+            continue;
+        }
+
+        // Two repositories can render to the same path in either layout.
+        if to.exists() || moves.iter().any(|m: &Move| m.to == to) {
+            conflicts.push(to);
+        } else {
+            moves.push(Move { from, to });
+        }
+    }
+
+    (moves, conflicts)
+}
+
+/// Prints and makes `moves`, or only prints them on a dry run. Returns how
+/// many failed.
+async fn apply_moves(moves: &[Move], directory: &Path, dry_run: bool) -> usize {
+    let mut failed = 0;
+    for Move { from, to } in moves {
+        eprintln!("  {} -> {}", relative(directory, from), relative(directory, to));
+        if dry_run {
+            continue;
+        }
+
+        if let Err(e) = move_clone(from, to).await {
+            eprintln!("    failed: {e:#}");
+            failed += 1;
+            continue;
+        }
+        remove_empty_parents(from, directory);
+    }
+
+    failed
+}
+
+async fn move_clone(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::rename(from, to).await?;
+
+    // Worktrees and their bare repository point at each other by absolute
+    // path, so they need repairing after a move.
+    let bare = to.join(".bare");
+    if bare.exists() {
+        let worktrees = std::fs::read_dir(to)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join(".git").is_file());
+
+        let output = tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(&bare)
+            .args(["worktree", "repair"])
+            .args(worktrees)
+            .output()
+            .await?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "moved, but failed to repair worktrees: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the directories that held `path`, up to `root`, while they are
+/// empty.
+pub fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if dir == root || !dir.starts_with(root) || std::fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+pub fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).display().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(owner: &str, repo_name: &str) -> Repository {
+        Repository {
+            provider: "github.com".into(),
+            owner: owner.into(),
+            repo_name: repo_name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn moves_clones_and_skips_taken_targets() -> anyhow::Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("gitnow-migrate-{}", uuid::Uuid::new_v4()));
+        for path in ["kjuulh/gitnow/.git", "kjuulh/taken/.git", "github.com/kjuulh/taken"] {
+            std::fs::create_dir_all(directory.join(path))?;
+        }
+        let repositories = [repository("kjuulh", "gitnow"), repository("kjuulh", "taken")];
+        let layout = PathLayout::new(&directory);
+        let previous = layout.clone().with_template("{{ owner }}/{{ repo }}");
+
+        let (moves, conflicts) = plan_moves(&repositories, &previous, &layout);
+        assert_eq!(conflicts, [directory.join("github.com/kjuulh/taken")]);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].from, directory.join("kjuulh/gitnow"));
+        assert_eq!(moves[0].to, directory.join("github.com/kjuulh/gitnow"));
+
+        assert_eq!(apply_moves(&moves, &directory, true).await, 0);
+        assert!(directory.join("kjuulh/gitnow/.git").exists());
+        assert!(!directory.join("github.com/kjuulh/gitnow").exists());
+
+        assert_eq!(apply_moves(&moves, &directory, false).await, 0);
+        assert!(directory.join("github.com/kjuulh/gitnow/.git").exists());
+        assert!(!directory.join("kjuulh/gitnow").exists());
+        // The old owner directory still holds the skipped clone.
+        assert!(directory.join("kjuulh/taken/.git").exists());
+
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn removes_empty_parents_up_to_the_root() -> anyhow::Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("gitnow-migrate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(directory.join("a/b/c"))?;
+        std::fs::create_dir_all(directory.join("a/other"))?;
+        std::fs::remove_dir(directory.join("a/b/c"))?;
+
+        remove_empty_parents(&directory.join("a/b/c"), &directory);
+        assert!(!directory.join("a/b").exists());
+        assert!(directory.join("a/other").exists());
+
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeSet;
+
+use regex::Regex;
+
+use crate::{
+    app::App,
+    cache::load_repositories,
+    components::task_list::{TaskList, TaskState},
+    mirror::{Mirror, MirrorsApp},
+    theme::ThemeApp,
+};
+
+/// Mirrors refreshed at once.
+const REFRESH_CONCURRENCY: usize = 5;
+
+#[derive(clap::Parser)]
+pub struct MirrorsCommand {
+    #[command(subcommand)]
+    command: MirrorsSubcommand,
+}
+
+#[derive(clap::Subcommand)]
+enum MirrorsSubcommand {
+    /// Fetch new objects into every mirror
+    Refresh(MirrorsRefreshCommand),
+    /// Delete mirrors of repositories that no provider lists anymore
+    Prune(MirrorsPruneCommand),
+}
+
+#[derive(clap::Parser)]
+pub struct MirrorsRefreshCommand {
+    /// Regex matched against `provider/owner/repo`
+    #[arg(long = "search")]
+    search: Option<String>,
+}
+
+#[derive(clap::Parser)]
+pub struct MirrorsPruneCommand {
+    /// Delete every mirror, not just those of removed repositories
+    #[arg(long = "all", default_value = "false")]
+    all: bool,
+
+    /// Print the mirrors that would be deleted without deleting them
+    #[arg(long = "dry-run", default_value = "false")]
+    dry_run: bool,
+}
+
+impl MirrorsCommand {
+    pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        match &self.command {
+            MirrorsSubcommand::Refresh(refresh) => refresh.execute(app).await,
+            MirrorsSubcommand::Prune(prune) => prune.execute(app).await,
+        }
+    }
+}
+
+impl MirrorsRefreshCommand {
+    async fn execute(&self, app: &'static App) -> anyhow::Result<()> {
+        let mut mirrors = app.mirrors().list()?;
+        if let Some(search) = &self.search {
+            let search = Regex::new(search)?;
+            mirrors.retain(|m| search.is_match(&m.rel_path));
+        }
+        if mirrors.is_empty() {
+            eprintln!("no mirrors found in {}", app.mirrors().directory()?.display());
+            return Ok(());
+        }
+
+        let labels = mirrors.iter().map(|m| m.rel_path.clone()).collect();
+        let states = TaskList::new(labels)
+            .with_theme(app.theme())
+            .execute(REFRESH_CONCURRENCY, |i| {
+                let mirror = mirrors[i].clone();
+                async move { app.mirrors().refresh(&mirror).await }
+            })
+            .await?;
+
+        let failed: Vec<_> = mirrors
+            .iter()
+            .zip(states)
+            .filter_map(|(mirror, state)| match state {
+                TaskState::Failed(e) => Some(e),
+                TaskState::Cancelled => Some(format!("{}: cancelled", mirror.rel_path)),
+                _ => None,
+            })
+            .collect();
+
+        eprintln!(
+            "refreshed {} of {} mirrors",
+            mirrors.len() - failed.len(),
+            mirrors.len()
+        );
+        for e in &failed {
+            eprintln!("  ✗ {e}");
+        }
+        if !failed.is_empty() {
+            anyhow::bail!("failed to refresh {} mirrors", failed.len());
+        }
+
+        Ok(())
+    }
+}
+
+impl MirrorsPruneCommand {
+    async fn execute(&self, app: &'static App) -> anyhow::Result<()> {
+        let mirrors = app.mirrors().list()?;
+
+        let stale: Vec<Mirror> = if self.all {
+            mirrors
+        } else {
+            let known: BTreeSet<String> = load_repositories(app, true)
+                .await?
+                .iter()
+                .map(|r| r.to_rel_path().display().to_string())
+                .collect();
+
+            mirrors
+                .into_iter()
+                .filter(|m| !known.contains(&m.rel_path))
+                .collect()
+        };
+
+        if stale.is_empty() {
+            eprintln!("no mirrors to prune");
+            return Ok(());
+        }
+
+        // Clones dissociate from their mirror, so deleting it is always safe.
+        let mut deleted = 0;
+        for mirror in &stale {
+            if self.dry_run {
+                println!("{}", mirror.path.display());
+                continue;
+            }
+
+            match tokio::fs::remove_dir_all(&mirror.path).await {
+                Ok(()) => {
+                    eprintln!("  deleted {}", mirror.rel_path);
+                    deleted += 1;
+                }
+                Err(e) => eprintln!("  failed to delete {}: {e}", mirror.rel_path),
+            }
+        }
+
+        if self.dry_run {
+            eprintln!("would delete {} mirror(s)", stale.len());
+        } else {
+            eprintln!("deleted {deleted} mirror(s)");
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 use chrono::Utc;
 use futures::{StreamExt, stream};
-use std::collections::HashMap;
+use ratatui::{
+    style::Stylize,
+    text::{Line, Text},
+};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 
 use crate::{
     app::App,
@@ -10,13 +12,15 @@ use crate::{
     chooser::Chooser,
     custom_command::CustomCommandApp,
     fuzzy_matcher::FuzzyMatcherApp,
+    git_clone::find_clone_profile,
+    history::{HistoryApp, HistoryKind},
     interactive::{InteractiveApp, Searchable},
+    query::QueryContext,
     project_metadata::{ProjectMetadata, RepoEntry},
     shell::ShellApp,
-    template_command,
 };
 
-use super::root::RepositoryMatcher;
+use super::{clone::clone_all, root::RepositoryMatcher};
 
 #[derive(clap::Parser)]
 pub struct ProjectCommand {
@@ -72,6 +76,10 @@ pub struct ProjectCreateCommand {
     /// Skip spawning a shell in the project directory
     #[arg(long = "no-shell", default_value = "false")]
     no_shell: bool,
+
+    /// Clone with this entry of settings.clone_profiles
+    #[arg(long = "profile")]
+    profile: Option<String>,
 }
 
 #[derive(clap::Parser)]
@@ -162,6 +170,33 @@ impl Searchable for DirEntry {
             None => self.name.clone(),
         }
     }
+
+    fn preview(&self, _context: &QueryContext) -> Option<Text<'static>> {
+        let mut lines = vec![Line::from(self.name.clone()).bold()];
+
+        let Some(meta) = &self.metadata else {
+            lines.push(Line::from("no project metadata").dark_gray());
+            return Some(Text::from(lines));
+        };
+
+        lines.push(Line::from(format!("created {}", meta.created_ago())).dark_gray());
+        if let Some(template) = &meta.template {
+            lines.push(Line::from(format!("template: {template}")));
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from(format!("repositories ({})", meta.repositories.len())).bold());
+        for repo in &meta.repositories {
+            let label = format!("{}/{}/{}", repo.provider, repo.owner, repo.repo_name);
+            lines.push(if self.path.join(&repo.repo_name).exists() {
+                Line::from(format!("● {label}")).green()
+            } else {
+                Line::from(format!("○ {label}")).dark_gray()
+            });
+        }
+
+        Some(Text::from(lines))
+    }
 }
 
 fn parse_cutoff_date(value: &str) -> Result<chrono::DateTime<Utc>, String> {
@@ -190,7 +225,7 @@ fn resolve_dir(configured: Option<&str>, default: &str) -> PathBuf {
     dirs::home_dir().unwrap_or_default().join(default)
 }
 
-fn get_projects_dir(app: &'static App) -> PathBuf {
+pub fn get_projects_dir(app: &'static App) -> PathBuf {
     let configured = app
         .config
         .settings
@@ -261,18 +296,65 @@ fn projects_created_before(projects: &[DirEntry], cutoff: chrono::DateTime<Utc>)
 
 const PROJECT_DELETE_CONCURRENCY: usize = 4;
 
-async fn delete_project_directories<'a, I>(projects: I) -> Vec<(&'a DirEntry, std::io::Result<()>)>
+/// Deletes the projects concurrently, running their pre and post delete
+/// hooks one at a time around that. A project whose pre delete hook fails
+/// with `fail_on_error` is kept.
+async fn delete_project_directories<'a, I>(
+    app: &'static App,
+    projects: I,
+) -> Vec<(&'a DirEntry, anyhow::Result<()>)>
 where
     I: IntoIterator<Item = &'a DirEntry>,
 {
-    stream::iter(projects)
+    let hooks = app.project_hooks();
+    let mut results = Vec::new();
+    let mut ready = Vec::new();
+    for project in projects {
+        match hooks
+            .execute_pre_delete_command(&project.name, &project.path, &repo_labels(project))
+            .await
+        {
+            Ok(()) => ready.push(project),
+            Err(e) => results.push((project, Err(e))),
+        }
+    }
+
+    let deleted: Vec<_> = stream::iter(ready)
         .map(|project| async move {
             let result = tokio::fs::remove_dir_all(&project.path).await;
             (project, result)
         })
         .buffered(PROJECT_DELETE_CONCURRENCY)
         .collect()
-        .await
+        .await;
+
+    for (project, result) in deleted {
+        let result = match result {
+            Ok(()) => {
+                hooks
+                    .execute_post_delete_command(
+                        &project.name,
+                        &project.path,
+                        &repo_labels(project),
+                    )
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        results.push((project, result));
+    }
+
+    results
+}
+
+/// The `provider/owner/repo` paths of the project's repositories.
+fn repo_labels(project: &DirEntry) -> Vec<String> {
+    project
+        .metadata
+        .iter()
+        .flat_map(|metadata| &metadata.repositories)
+        .map(|repo| repo.display_label())
+        .collect()
 }
 
 fn copy_dir_recursive(src: &Path, dst: &Path) -> anyhow::Result<()> {
@@ -294,75 +376,24 @@ async fn clone_repos_into(
     app: &'static App,
     repos: &[crate::git_provider::Repository],
     target_dir: &Path,
+    profile: Option<String>,
 ) -> anyhow::Result<()> {
-    let clone_template = app
-        .config
-        .settings
-        .clone_command
-        .as_deref()
-        .unwrap_or(template_command::DEFAULT_CLONE_COMMAND);
-
-    let concurrency_limit = Arc::new(tokio::sync::Semaphore::new(5));
-    let mut handles = Vec::new();
-
-    for repo in repos {
-        let repo = repo.clone();
-        let target_dir = target_dir.to_path_buf();
-        let clone_template = clone_template.to_string();
-        let concurrency = Arc::clone(&concurrency_limit);
-        let custom_command = app.custom_command();
-
-        let handle = tokio::spawn(async move {
-            let _permit = concurrency.acquire().await?;
-
-            let clone_path = target_dir.join(&repo.repo_name);
-
-            if clone_path.exists() {
-                eprintln!("  {} already exists, skipping", repo.repo_name);
-                return Ok::<(), anyhow::Error>(());
-            }
-
-            eprintln!("  cloning {}...", repo.to_rel_path().display());
-
-            let path_str = clone_path.display().to_string();
-            let context = HashMap::from([
-                ("ssh_url", repo.ssh_url.as_str()),
-                ("path", path_str.as_str()),
-            ]);
-
-            let output = template_command::render_and_execute(&clone_template, context).await?;
-
-            if !output.status.success() {
-                let stderr = std::str::from_utf8(&output.stderr).unwrap_or_default();
-                anyhow::bail!("failed to clone {}: {}", repo.repo_name, stderr);
-            }
-
-            custom_command
-                .execute_post_clone_command(&clone_path)
-                .await?;
-
-            Ok(())
-        });
-
-        handles.push(handle);
-    }
+    let targets = repos
+        .iter()
+        .map(|repo| (repo.clone(), target_dir.join(&repo.repo_name)))
+        .collect();
 
-    let results = futures::future::join_all(handles).await;
-    for res in results {
-        match res {
-            Ok(Ok(())) => {}
-            Ok(Err(e)) => {
-                tracing::error!("clone error: {}", e);
-                eprintln!("error: {}", e);
-            }
-            Err(e) => {
-                tracing::error!("task error: {}", e);
-                eprintln!("error: {}", e);
-            }
-        }
-    }
+    clone_all(app, targets, profile).await
+}
 
-    Ok(())
+/// Entries for the repositories of `repos` that are cloned into `target_dir`,
+/// to record in the project's metadata even when others failed to clone.
+fn cloned_entries(repos: &[crate::git_provider::Repository], target_dir: &Path) -> Vec<RepoEntry> {
+    repos
+        .iter()
+        .filter(|repo| target_dir.join(&repo.repo_name).exists())
+        .map(RepoEntry::from)
+        .collect()
 }
 
 /// Helper to select an existing project, either by name or interactively.
@@ -410,10 +441,10 @@ async fn auto_delete_old_projects(app: &'static App) -> anyhow::Result<()> {
         eprintln!("  - {} ({})", project.name, project.path.display());
     }
 
-    for (project, result) in delete_project_directories(matching).await {
+    for (project, result) in delete_project_directories(app, matching).await {
         result.map_err(|error| {
             anyhow::anyhow!(
-                "failed to automatically delete project '{}': {}",
+                "failed to automatically delete project '{}': {:#}",
                 project.name,
                 error
             )
@@ -453,30 +484,28 @@ impl ProjectCommand {
         }
 
         let project = match &self.search {
-            Some(needle) => {
-                let matched = projects
-                    .iter()
-                    .find(|p| p.name.contains(needle.as_str()))
-                    .or_else(|| {
-                        projects.iter().find(|p| {
-                            p.name
-                                .to_lowercase()
-                                .contains(&needle.to_lowercase())
-                        })
-                    })
-                    .ok_or(anyhow::anyhow!(
-                        "no project matching '{}' found",
-                        needle
-                    ))?
-                    .clone();
-                matched
-            }
+            Some(needle) => projects
+                .iter()
+                .find(|p| p.name.contains(needle.as_str()))
+                .or_else(|| {
+                    projects
+                        .iter()
+                        .find(|p| p.name.to_lowercase().contains(&needle.to_lowercase()))
+                })
+                .ok_or(anyhow::anyhow!("no project matching '{}' found", needle))?
+                .clone(),
             None => app
                 .interactive()
                 .interactive_search_items(&projects)?
                 .ok_or(anyhow::anyhow!("no project selected"))?,
         };
 
+        app.history()
+            .try_record(HistoryKind::Project, project.name.clone(), &project.path);
+        app.project_hooks()
+            .execute_on_enter_command(&project.name, &project.path)
+            .await?;
+
         if !self.no_shell && !chooser.is_active() {
             app.shell().spawn_shell_at(&project.path).await?;
         } else {
@@ -504,8 +533,7 @@ impl ProjectCreateCommand {
         };
 
         let dir_name = name
-            .replace(' ', "-")
-            .replace('/', "-")
+            .replace([' ', '/'], "-")
             .to_lowercase();
 
         let projects_dir = get_projects_dir(app);
@@ -519,6 +547,10 @@ impl ProjectCreateCommand {
             );
         }
 
+        if let Some(profile) = &self.profile {
+            find_clone_profile(app, profile)?;
+        }
+
         let repositories = load_repositories(app, !self.no_cache).await?;
 
         let selected_repos = if !self.repos.is_empty() {
@@ -545,9 +577,37 @@ impl ProjectCreateCommand {
             anyhow::bail!("no repositories selected");
         }
 
+        let hooks = app.project_hooks();
+        let repo_labels: Vec<String> = selected_repos
+            .iter()
+            .map(|repo| repo.to_rel_path().display().to_string())
+            .collect();
+        hooks
+            .execute_pre_create_command(&dir_name, &project_path, &repo_labels)
+            .await?;
+
         tokio::fs::create_dir_all(&project_path).await?;
 
-        clone_repos_into(app, &selected_repos, &project_path).await?;
+        if let Err(e) =
+            clone_repos_into(app, &selected_repos, &project_path, self.profile.take()).await
+        {
+            let cloned = cloned_entries(&selected_repos, &project_path);
+            if cloned.is_empty() {
+                tokio::fs::remove_dir_all(&project_path).await?;
+                return Err(e);
+            }
+
+            // Keep the repositories that did clone, so the project can be
+            // completed with `project add` instead of starting over.
+            let complete = cloned.len() == selected_repos.len();
+            ProjectMetadata::new(dir_name.clone(), None, cloned).save(&project_path)?;
+            if complete {
+                return Err(e);
+            }
+            return Err(e.context(format!(
+                "project '{dir_name}' was created with the repositories that cloned, add the others with 'gitnow project add {dir_name}'"
+            )));
+        }
 
         // Apply template if requested
         let templates_dir = get_templates_dir(app);
@@ -594,6 +654,10 @@ impl ProjectCreateCommand {
         let metadata = ProjectMetadata::new(dir_name.clone(), template_name, repo_entries);
         metadata.save(&project_path)?;
 
+        hooks
+            .execute_post_create_command(&dir_name, &project_path, &repo_labels)
+            .await?;
+
         eprintln!(
             "project '{}' created at {} with {} repositories",
             dir_name,
@@ -601,6 +665,12 @@ impl ProjectCreateCommand {
             selected_repos.len()
         );
 
+        app.history()
+            .try_record(HistoryKind::Project, dir_name.clone(), &project_path);
+        hooks
+            .execute_on_enter_command(&dir_name, &project_path)
+            .await?;
+
         if !self.no_shell && !chooser.is_active() {
             app.shell().spawn_shell_at(&project_path).await?;
         } else {
@@ -611,18 +681,67 @@ impl ProjectCreateCommand {
     }
 }
 
-impl ProjectAddCommand {
-    async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
-        let projects_dir = get_projects_dir(app);
-        let projects = list_subdirectories(&projects_dir)?;
+/// Lists existing projects, failing when there are none.
+fn existing_projects(app: &'static App) -> anyhow::Result<Vec<DirEntry>> {
+    let projects_dir = get_projects_dir(app);
+    let projects = list_subdirectories(&projects_dir)?;
 
-        if projects.is_empty() {
-            anyhow::bail!(
-                "no projects found in {}. Use 'gitnow project create' to create one.",
-                projects_dir.display()
-            );
-        }
+    if projects.is_empty() {
+        anyhow::bail!(
+            "no projects found in {}. Use 'gitnow project create' to create one.",
+            projects_dir.display()
+        );
+    }
 
+    Ok(projects)
+}
+
+/// Clones `repos` into `project` and records them in its metadata.
+async fn add_repositories(
+    app: &'static App,
+    project: &DirEntry,
+    repos: &[crate::git_provider::Repository],
+) -> anyhow::Result<()> {
+    let cloned = clone_repos_into(app, repos, &project.path, None).await;
+
+    // Record the repositories that did clone, even when others failed.
+    if let Some(mut metadata) = ProjectMetadata::load(&project.path) {
+        metadata.add_repositories(cloned_entries(repos, &project.path));
+        metadata.save(&project.path)?;
+    }
+    cloned?;
+
+    let repo_labels: Vec<String> = repos
+        .iter()
+        .map(|repo| repo.to_rel_path().display().to_string())
+        .collect();
+    app.project_hooks()
+        .execute_post_add_command(&project.name, &project.path, &repo_labels)
+        .await?;
+
+    eprintln!(
+        "added {} repositories to project '{}'",
+        repos.len(),
+        project.name
+    );
+
+    Ok(())
+}
+
+/// Adds `repos` to a project picked interactively.
+pub async fn add_to_selected_project(
+    app: &'static App,
+    repos: &[crate::git_provider::Repository],
+) -> anyhow::Result<()> {
+    let projects = existing_projects(app)?;
+    let project = select_project(app, None, &projects)?;
+
+    add_repositories(app, &project, repos).await
+}
+
+impl ProjectAddCommand {
+    async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        let projects = existing_projects(app)?;
         let project = select_project(app, self.name.take(), &projects)?;
 
         let repositories = load_repositories(app, !self.no_cache).await?;
@@ -651,21 +770,7 @@ impl ProjectAddCommand {
             anyhow::bail!("no repositories selected");
         }
 
-        clone_repos_into(app, &selected_repos, &project.path).await?;
-
-        if let Some(mut metadata) = ProjectMetadata::load(&project.path) {
-            let new_entries: Vec<RepoEntry> = selected_repos.iter().map(RepoEntry::from).collect();
-            metadata.add_repositories(new_entries);
-            metadata.save(&project.path)?;
-        }
-
-        eprintln!(
-            "added {} repositories to project '{}'",
-            selected_repos.len(),
-            project.name
-        );
-
-        Ok(())
+        add_repositories(app, &project, &selected_repos).await
     }
 }
 
@@ -762,6 +867,11 @@ impl ProjectRemoveCommand {
             meta.save(&project.path)?;
         }
 
+        let repo_labels: Vec<String> = selected.iter().map(|repo| repo.display_label()).collect();
+        app.project_hooks()
+            .execute_post_remove_command(&project.name, &project.path, &repo_labels)
+            .await?;
+
         eprintln!(
             "removed {} repositories from project '{}'",
             removed,
@@ -838,11 +948,11 @@ impl ProjectListCommand {
                 } else {
                     println!("{}", project.name);
                 }
-                if self.repos {
-                    if let Some(meta) = &project.metadata {
-                        for repo in &meta.repositories {
-                            println!("  {}/{}/{}", repo.provider, repo.owner, repo.repo_name);
-                        }
+                if self.repos
+                    && let Some(meta) = &project.metadata
+                {
+                    for repo in &meta.repositories {
+                        println!("  {}/{}/{}", repo.provider, repo.owner, repo.repo_name);
                     }
                 }
             }
@@ -928,7 +1038,7 @@ impl ProjectDeleteCommand {
         }
 
         let mut deleted = 0;
-        for (project, result) in delete_project_directories(&selected).await {
+        for (project, result) in delete_project_directories(app, &selected).await {
             match result {
                 Ok(()) => {
                     if !self.quiet {
@@ -937,7 +1047,7 @@ impl ProjectDeleteCommand {
                     deleted += 1;
                 }
                 Err(e) => {
-                    eprintln!("  failed to delete {}: {}", project.name, e);
+                    eprintln!("  failed to delete {}: {:#}", project.name, e);
                 }
             }
         }
@@ -0,0 +1,406 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use futures::{StreamExt, stream};
+
+use crate::{
+    app::App,
+    cache::load_repositories,
+    commands::{
+        migrate_layout::{relative, remove_empty_parents},
+        project::get_projects_dir,
+    },
+    git_provider::Repository,
+    git_repo::GitRepo,
+    history::{HistoryApp, HistoryEntry},
+    interactive::{InteractiveApp, Searchable},
+    layout::PathLayoutApp,
+    local_clones::{checkouts, find_clone_directories},
+};
+
+/// Clones inspected at once.
+const PRUNE_CONCURRENCY: usize = 16;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(clap::Parser)]
+pub struct PruneCommand {
+    /// Also flag clones that weren't used for this many days
+    #[arg(long = "older-than")]
+    older_than: Option<u64>,
+
+    /// Print the clones that would be deleted without deleting them
+    #[arg(long = "dry-run", default_value = "false")]
+    dry_run: bool,
+
+    /// Delete every prunable clone without picking or confirming. Clones
+    /// not known to be gone upstream are left alone
+    #[arg(long = "force", default_value = "false")]
+    force: bool,
+
+    /// Skip cache
+    #[arg(long = "no-cache", default_value = "false")]
+    no_cache: bool,
+}
+
+/// Why a clone is up for pruning.
+#[derive(Debug, Clone, PartialEq)]
+enum Reason {
+    /// No provider lists the repository anymore, and its remote says it
+    /// doesn't exist.
+    Gone,
+    Archived,
+    /// Days since the clone was last used.
+    Unused(u64),
+    /// No provider lists the repository, but it isn't known to be gone: its
+    /// owner isn't configured, or its remote couldn't tell. Never deleted
+    /// with `--force`.
+    Unknown,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Gone => write!(f, "no longer exists upstream"),
+            Reason::Archived => write!(f, "archived upstream"),
+            Reason::Unused(days) => write!(f, "unused for {days} days"),
+            Reason::Unknown => write!(f, "not listed by any provider, unknown upstream"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    /// Path relative to the projects directory.
+    name: String,
+    path: PathBuf,
+    reason: Reason,
+    /// Work that would be lost by deleting the clone, if any.
+    kept_for: Option<String>,
+}
+
+impl Searchable for Candidate {
+    fn display_label(&self) -> String {
+        format!("{} ({})", self.name, self.reason)
+    }
+}
+
+impl PruneCommand {
+    pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        let repositories = load_repositories(app, !self.no_cache).await?;
+        if repositories.is_empty() {
+            anyhow::bail!(
+                "no repositories in the cache to compare clones against. Run 'gitnow update' first."
+            );
+        }
+
+        let layout = app.layout();
+        let directory = layout.directory();
+        let known: HashMap<PathBuf, &Repository> = repositories
+            .iter()
+            .map(|repository| (layout.path(repository), repository))
+            .collect();
+        let owners: HashSet<&str> = repositories
+            .iter()
+            .filter_map(|repository| owner_url(&repository.ssh_url))
+            .collect();
+        // Scratch-pad projects have `gitnow project delete`.
+        let projects_dir = get_projects_dir(app);
+        let history = app.history().entries();
+        let older_than = self.older_than;
+
+        let clone_directories = {
+            let (directory, depth) = (directory.to_path_buf(), layout.depth());
+            tokio::task::spawn_blocking(move || {
+                find_clone_directories(&directory, depth, &projects_dir)
+            })
+            .await?
+        };
+
+        let candidates: Vec<Candidate> = stream::iter(clone_directories)
+            .map(|path| {
+                let repository = known.get(&path).copied();
+                let (owners, history) = (&owners, &history);
+                async move {
+                    let reason = reason(&path, repository, owners, older_than, history).await?;
+                    Some(Candidate {
+                        name: relative(directory, &path),
+                        kept_for: unsaved_work(&path).await,
+                        path,
+                        reason,
+                    })
+                }
+            })
+            .buffered(PRUNE_CONCURRENCY)
+            .filter_map(std::future::ready)
+            .collect()
+            .await;
+
+        let (prunable, kept): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|candidate| candidate.kept_for.is_none());
+        for candidate in &kept {
+            eprintln!(
+                "  kept {} ({}): {}",
+                candidate.name,
+                candidate.reason,
+                candidate.kept_for.as_deref().unwrap_or_default()
+            );
+        }
+        if prunable.is_empty() {
+            eprintln!("no clones to prune");
+            return Ok(());
+        }
+
+        if self.dry_run {
+            for candidate in &prunable {
+                eprintln!("  - {} ({})", candidate.name, candidate.reason);
+            }
+            eprintln!("would delete {} clone(s)", prunable.len());
+            let unknown = prunable
+                .iter()
+                .filter(|candidate| candidate.reason == Reason::Unknown)
+                .count();
+            if unknown > 0 {
+                eprintln!("{unknown} of them only when picked, --force leaves them alone");
+            }
+            return Ok(());
+        }
+
+        let selected = if self.force {
+            let (unknown, selected): (Vec<_>, Vec<_>) = prunable
+                .into_iter()
+                .partition(|candidate| candidate.reason == Reason::Unknown);
+            for candidate in &unknown {
+                eprintln!("  skipped {} ({})", candidate.name, candidate.reason);
+            }
+            selected
+        } else {
+            eprintln!("Select clones to delete (Tab to toggle, Enter to confirm):");
+            app.interactive().interactive_multi_search(&prunable)?
+        };
+        if selected.is_empty() {
+            eprintln!("no clones selected");
+            return Ok(());
+        }
+
+        eprintln!("Clones to delete:");
+        for candidate in &selected {
+            eprintln!("  - {} ({})", candidate.name, candidate.reason);
+        }
+
+        if !self.force {
+            eprint!("Proceed? [y/N] ");
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            if !input.trim().eq_ignore_ascii_case("y") {
+                eprintln!("aborted");
+                return Ok(());
+            }
+        }
+
+        let mut deleted = 0;
+        for candidate in &selected {
+            match tokio::fs::remove_dir_all(&candidate.path).await {
+                Ok(()) => {
+                    eprintln!("  deleted {}", candidate.name);
+                    remove_empty_parents(&candidate.path, directory);
+                    deleted += 1;
+                }
+                Err(e) => eprintln!("  failed to delete {}: {e}", candidate.name),
+            }
+        }
+        eprintln!("deleted {deleted} clone(s)");
+
+        Ok(())
+    }
+}
+
+/// Why the clone at `path` is up for pruning, if it is. `repository` is the
+/// cached repository cloned there, `owners` the [`owner_url`]s of every cached
+/// repository, and unused clones are only flagged with `older_than` days.
+async fn reason(
+    path: &Path,
+    repository: Option<&Repository>,
+    owners: &HashSet<&str>,
+    older_than: Option<u64>,
+    history: &[HistoryEntry],
+) -> Option<Reason> {
+    let Some(repository) = repository else {
+        return Some(gone_or_unknown(path, owners).await);
+    };
+    if repository.archived {
+        return Some(Reason::Archived);
+    }
+
+    let days = older_than?;
+    let unused = SystemTime::now()
+        .duration_since(last_used(path, history).await?)
+        .unwrap_or_default();
+    (unused >= Duration::from_secs(days * SECONDS_PER_DAY))
+        .then_some(Reason::Unused(unused.as_secs() / SECONDS_PER_DAY))
+}
+
+/// A clone no provider lists is only gone when its `origin` belongs to an
+/// owner the providers list repositories for, and says the repository doesn't
+/// exist. Otherwise it may be cloned from elsewhere, or the remote couldn't be
+/// reached.
+async fn gone_or_unknown(path: &Path, owners: &HashSet<&str>) -> Reason {
+    let (repository, _) = repository_and_checkouts(path);
+    let repo = GitRepo::new(repository);
+
+    let Ok(url) = repo.origin_url().await else {
+        return Reason::Unknown;
+    };
+    if !owner_url(&url).is_some_and(|owner| owners.contains(owner)) {
+        return Reason::Unknown;
+    }
+    match repo.origin_exists().await {
+        Ok(false) => Reason::Gone,
+        Ok(true) | Err(_) => Reason::Unknown,
+    }
+}
+
+/// The clone URL up to the owner, e.g. `git@github.com:kjuulh` for
+/// `git@github.com:kjuulh/gitnow.git`.
+fn owner_url(url: &str) -> Option<&str> {
+    url.trim_end_matches('/')
+        .rsplit_once('/')
+        .map(|(owner, _)| owner)
+}
+
+/// A regular clone, or the `.bare` repository and its worktrees.
+fn repository_and_checkouts(path: &Path) -> (PathBuf, Vec<PathBuf>) {
+    let bare = path.join(".bare");
+    if bare.is_dir() {
+        (bare, checkouts(path))
+    } else {
+        (path.to_path_buf(), vec![path.to_path_buf()])
+    }
+}
+
+/// When the clone at `path` was last used: the newest git activity in any of
+/// its checkouts, or the last time gitnow opened something inside it.
+async fn last_used(path: &Path, history: &[HistoryEntry]) -> Option<SystemTime> {
+    let (repository, checkouts) = repository_and_checkouts(path);
+
+    let mut newest = GitRepo::new(repository).last_activity().await;
+    for checkout in checkouts {
+        newest = newest.max(GitRepo::new(checkout).last_activity().await);
+    }
+    let opened = history
+        .iter()
+        .filter(|entry| entry.path.starts_with(path))
+        .map(|entry| SystemTime::from(entry.timestamp))
+        .max();
+
+    newest.max(opened)
+}
+
+/// Uncommitted changes, stashes or commits that aren't on any remote, which
+/// deleting the clone at `path` would lose. `None` if there are none.
+async fn unsaved_work(path: &Path) -> Option<String> {
+    let (repository, checkouts) = repository_and_checkouts(path);
+
+    for checkout in checkouts {
+        let status = match GitRepo::new(&checkout).status().await {
+            Ok(status) => status,
+            Err(e) => return Some(format!("failed to check for changes: {e:#}")),
+        };
+        let location = if checkout == path {
+            String::new()
+        } else {
+            format!(" in {}", relative(path, &checkout))
+        };
+
+        if status.is_dirty() {
+            return Some(format!("{} uncommitted changes{location}", status.changes));
+        }
+        if status.stashes > 0 {
+            return Some(format!("{} stashes{location}", status.stashes));
+        }
+    }
+
+    match GitRepo::new(&repository).unpushed_commits().await {
+        Ok(0) => None,
+        Ok(commits) => Some(format!("{commits} unpushed commits")),
+        Err(e) => Some(format!("failed to check for unpushed commits: {e:#}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flags_clones_gone_or_archived_upstream() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("gitnow-prune-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("clone");
+        tokio::fs::create_dir_all(&path).await?;
+        let origin = dir.join("kjuulh").join("deleted.git");
+        for args in [
+            &["init", "--quiet"][..],
+            &["remote", "add", "origin", &origin.display().to_string()],
+        ] {
+            let status = tokio::process::Command::new("git")
+                .arg("-C")
+                .arg(&path)
+                .args(args)
+                .status()
+                .await?;
+            assert!(status.success());
+        }
+
+        let owner = dir.join("kjuulh").display().to_string();
+        let configured = HashSet::from([owner.as_str()]);
+        let active = Repository::default();
+        let archived = Repository {
+            archived: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            reason(&path, None, &configured, None, &[]).await,
+            Some(Reason::Gone)
+        );
+        // Only configured owners are asked whether the repository still exists.
+        assert_eq!(
+            reason(&path, None, &HashSet::new(), None, &[]).await,
+            Some(Reason::Unknown)
+        );
+        assert_eq!(
+            reason(&dir.join("missing"), None, &configured, None, &[]).await,
+            Some(Reason::Unknown)
+        );
+        assert_eq!(
+            reason(&path, Some(&archived), &configured, None, &[]).await,
+            Some(Reason::Archived)
+        );
+        assert_eq!(reason(&path, Some(&active), &configured, None, &[]).await, None);
+        // Without any activity to go by, a clone is never flagged as unused.
+        assert_eq!(
+            reason(&dir.join("missing"), Some(&active), &configured, Some(0), &[]).await,
+            None
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+        Ok(())
+    }
+
+    #[test]
+    fn owner_url_strips_the_repository() {
+        assert_eq!(
+            owner_url("git@github.com:kjuulh/gitnow.git"),
+            Some("git@github.com:kjuulh")
+        );
+        assert_eq!(
+            owner_url("https://github.com/kjuulh/gitnow/"),
+            Some("https://github.com/kjuulh")
+        );
+        assert_eq!(owner_url("gitnow"), None);
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use crate::{
+    app::App,
+    cache::CacheApp,
+    chooser::Chooser,
+    custom_command::CustomCommandApp,
+    git_provider::Repository,
+    history::{HistoryApp, HistoryEntry, HistoryKind},
+    interactive::{InteractiveApp, Searchable},
+    shell::ShellApp,
+};
+
+#[derive(clap::Parser)]
+pub struct RecentCommand {
+    /// Maximum number of entries to show
+    #[arg(long = "limit", short = 'n', default_value = "50")]
+    limit: usize,
+
+    /// Output recently opened locations as JSON instead of opening a picker
+    #[arg(long = "json", default_value = "false")]
+    json: bool,
+
+    /// Skip spawning a shell in the selected directory
+    #[arg(long = "no-shell", default_value = "false")]
+    no_shell: bool,
+}
+
+impl Searchable for HistoryEntry {
+    fn display_label(&self) -> String {
+        match self.kind {
+            HistoryKind::Worktree => format!(
+                "{:<10} {} ({})",
+                self.kind.to_string(),
+                self.name,
+                self.path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            ),
+            _ => format!("{:<10} {}", self.kind.to_string(), self.name),
+        }
+    }
+}
+
+impl RecentCommand {
+    pub async fn execute(&mut self, app: &'static App, chooser: &Chooser) -> anyhow::Result<()> {
+        let entries: Vec<HistoryEntry> = app
+            .history()
+            .recent()
+            .into_iter()
+            .filter(|entry| entry.path.exists())
+            .take(self.limit)
+            .collect();
+
+        if self.json {
+            let entries: Vec<serde_json::Value> = entries
+                .iter()
+                .map(|entry| {
+                    serde_json::json!({
+                        "kind": entry.kind,
+                        "name": entry.name,
+                        "path": entry.path.display().to_string(),
+                        "opened_at": entry.timestamp.to_rfc3339(),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+            return Ok(());
+        }
+
+        if entries.is_empty() {
+            anyhow::bail!("no recently opened locations found");
+        }
+
+        let entry = app
+            .interactive()
+            .interactive_search_items(&entries)?
+            .ok_or(anyhow::anyhow!("no location selected"))?;
+
+        open_entry(app, chooser, self.no_shell, &entry).await
+    }
+}
+
+/// Re-opens a history entry, recording the visit again so it moves to the
+/// front of the history.
+pub async fn open_entry(
+    app: &'static App,
+    chooser: &Chooser,
+    no_shell: bool,
+    entry: &HistoryEntry,
+) -> anyhow::Result<()> {
+    app.history()
+        .try_record(entry.kind, entry.name.clone(), &entry.path);
+
+    match entry.kind {
+        HistoryKind::Project => {
+            app.project_hooks()
+                .execute_on_enter_command(&entry.name, &entry.path)
+                .await?
+        }
+        HistoryKind::Repository | HistoryKind::Worktree => {
+            let repository = find_repository(app, &entry.name).await;
+            app.custom_command(&repository)
+                .execute_on_enter_command(&entry.path, entry.kind)
+                .await?
+        }
+    }
+
+    if !no_shell && !chooser.is_active() {
+        app.shell().spawn_shell_at(&entry.path).await?;
+    } else {
+        chooser.set(&entry.path)?;
+    }
+
+    Ok(())
+}
+
+/// The repository at the `provider/owner/repo` path `name`, from the cache
+/// even when it has expired. Without a cached one, it's rebuilt from `name`,
+/// so hooks get no `ssh_url`.
+async fn find_repository(app: &'static App, name: &str) -> Repository {
+    let cached = app.cache().get_stale().await.ok().flatten();
+    if let Some(repository) = cached
+        .into_iter()
+        .flatten()
+        .find(|repository| repository.to_rel_path() == Path::new(name))
+    {
+        return repository;
+    }
+
+    let mut parts = name.splitn(3, '/');
+    Repository {
+        provider: parts.next().unwrap_or_default().into(),
+        owner: parts.next().unwrap_or_default().into(),
+        repo_name: parts.next().unwrap_or_default().into(),
+        ..Default::default()
+    }
+}
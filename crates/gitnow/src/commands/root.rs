@@ -1,29 +1,68 @@
-use std::{collections::BTreeMap, io::IsTerminal};
+use std::io::IsTerminal;
+use std::path::Path;
 
 use crate::{
     app::App,
-    cache::{load_repositories, CacheApp},
+    cache::{load_repositories, stream_repositories, CacheApp},
     chooser::Chooser,
     components::inline_command::InlineCommand,
+    config::AmbiguityAction,
     custom_command::CustomCommandApp,
     fuzzy_matcher::{FuzzyMatcher, FuzzyMatcherApp},
-    git_clone::GitCloneApp,
+    git_clone::{find_clone_profile, GitCloneApp},
     git_provider::Repository,
-    interactive::InteractiveApp,
+    history::{HistoryApp, HistoryKind},
+    interactive::{InteractiveApp, ItemLoader},
+    layout::PathLayoutApp,
     projects_list::ProjectsListApp,
+    query::Query,
     shell::ShellApp,
+    theme::ThemeApp,
+    updater::UpdaterApp,
 };
 
+use super::actions::RepositoryAction;
+
+const DEFAULT_AMBIGUITY_THRESHOLD: u32 = 8;
+
+/// Flags controlling how a non-interactive search picks a repository.
+#[derive(clap::Args, Debug, Default)]
+pub struct MatchArgs {
+    /// Only accept a repository whose name or path exactly equals the search
+    #[arg(long = "exact", default_value = "false")]
+    pub exact: bool,
+
+    /// Print every match with its score instead of opening one
+    #[arg(long = "list", default_value = "false")]
+    pub list: bool,
+
+    /// What to do when the best matches score too closely
+    /// (overrides settings.search.on_ambiguous)
+    #[arg(long = "on-ambiguous", value_enum)]
+    pub on_ambiguous: Option<AmbiguityAction>,
+}
+
 #[derive(Debug, Clone)]
 pub struct RootCommand {
     app: &'static App,
+    clone_profile: Option<String>,
 }
 
 impl RootCommand {
     pub fn new(app: &'static App) -> Self {
-        Self { app }
+        Self {
+            app,
+            clone_profile: None,
+        }
+    }
+
+    /// Clones with the named entry of `settings.clone_profiles`.
+    pub fn with_clone_profile(mut self, profile: Option<String>) -> Self {
+        self.clone_profile = profile;
+        self
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute(
         &mut self,
         search: Option<impl Into<String>>,
@@ -32,64 +71,88 @@ impl RootCommand {
         shell: bool,
         force_refresh: bool,
         force_cache_update: bool,
+        matching: &MatchArgs,
         chooser: &Chooser,
     ) -> anyhow::Result<()> {
         tracing::debug!("executing");
 
-        let repositories = if force_cache_update {
-            tracing::info!("forcing cache update...");
-            let repositories = self.app.projects_list().get_projects().await?;
-            self.app.cache().update(&repositories).await?;
-            repositories
-        } else {
-            load_repositories(self.app, cache).await?
-        };
+        if let Some(profile) = &self.clone_profile {
+            find_clone_profile(self.app, profile)?;
+        }
 
         let repo = match search {
             Some(needle) => {
-                let matched_repos = self
-                    .app
-                    .fuzzy_matcher()
-                    .match_repositories(&needle.into(), &repositories);
+                let repositories = if force_cache_update {
+                    tracing::info!("forcing cache update...");
+                    let repositories = self.app.projects_list().get_projects().await?;
+                    self.app.cache().update(&repositories).await?;
+                    repositories
+                } else {
+                    load_repositories(self.app, cache).await?
+                };
 
-                let repo = matched_repos
-                    .first()
-                    .ok_or(anyhow::anyhow!("failed to find repository"))?;
+                let Some(repo) =
+                    select_repository(self.app, &needle.into(), &repositories, matching)?
+                else {
+                    return Ok(());
+                };
                 tracing::debug!("selected repo: {}", repo.to_rel_path().display());
 
-                repo.to_owned()
+                repo
             }
             None => {
-                let repo = self
+                // Open the picker right away; without a usable cache the
+                // repositories stream in as each provider responds.
+                let cached = if cache && !force_cache_update {
+                    self.app.cache().get().await?
+                } else {
+                    None
+                };
+                let app = self.app;
+                let loader = ItemLoader::new(move || stream_repositories(app));
+                let (repositories, loader) = match cached {
+                    Some(repositories) => (repositories, loader),
+                    None => (Vec::new(), loader.load_on_open()),
+                };
+
+                let (repo, action) = self
                     .app
                     .interactive()
-                    .interactive_search(&repositories)?
+                    .interactive_search_with_loader(
+                        repositories,
+                        loader,
+                        RepositoryAction::labels(),
+                    )?
                     .ok_or(anyhow::anyhow!("failed to find a repository"))?;
 
                 tracing::debug!("selected repo: {}", repo.to_rel_path().display());
 
+                if let Some(action) = action.map(|i| RepositoryAction::ALL[i]) {
+                    return action.execute(self.app, chooser, &repo, shell).await;
+                }
+
                 repo
             }
         };
 
-        let project_path = self
-            .app
-            .config
-            .settings
-            .projects
-            .directory
-            .join(repo.to_rel_path());
+        let project_path = self.app.layout().path(&repo);
+        let hooks = self.app.custom_command(&repo);
         if !project_path.exists() {
             if clone {
-                let git_clone = self.app.git_clone();
+                let git_clone = self.app.git_clone().with_profile(self.clone_profile.clone());
+                hooks.execute_pre_clone_command(&project_path).await?;
 
                 if std::io::stdout().is_terminal() && shell {
                     let mut wrap_cmd =
-                        InlineCommand::new(format!("cloning: {}", repo.to_rel_path().display()));
+                        InlineCommand::new(format!("cloning: {}", repo.to_rel_path().display()))
+                        .with_theme(self.app.theme());
                     let repo = repo.clone();
                     wrap_cmd
-                        .execute(move || async move {
-                            git_clone.clone_repo(&repo, force_refresh).await?;
+                        .execute_with_progress(move |progress| async move {
+                            git_clone
+                                .with_progress(progress)
+                                .clone_repo(&repo, force_refresh)
+                                .await?;
 
                             Ok(())
                         })
@@ -97,21 +160,26 @@ impl RootCommand {
                 } else {
                     eprintln!("cloning repository...");
                     git_clone.clone_repo(&repo, force_refresh).await?;
-
-                    self.app
-                        .custom_command()
-                        .execute_post_clone_command(&project_path)
-                        .await?;
                 }
+
+                // Run after the spinner, so hook output doesn't garble it.
+                hooks.execute_post_clone_command(&project_path).await?;
             } else {
                 tracing::info!("skipping clone for repo: {}", &repo.to_rel_path().display());
             }
         } else {
             tracing::info!("repository already exists");
 
-            self.app
-                .custom_command()
-                .execute_post_update_command(&project_path)
+            if self.update_existing(&repo, &project_path, shell).await {
+                hooks.execute_post_update_command(&project_path).await?;
+            }
+        }
+
+        // Only record what can be reopened, not skipped or failed clones.
+        if project_path.exists() {
+            self.app.history().try_record_repository(&repo, &project_path);
+            hooks
+                .execute_on_enter_command(&project_path, HistoryKind::Repository)
                 .await?;
         }
 
@@ -119,11 +187,49 @@ impl RootCommand {
             self.app.shell().spawn_shell(&repo).await?;
         } else {
             tracing::info!("skipping shell for repo: {}", &repo.to_rel_path().display());
-            chooser.set(&self.app.config.settings.projects.directory.join(repo.to_rel_path()))?;
+            chooser.set(&project_path)?;
         }
 
         Ok(())
     }
+
+    /// Updates an existing clone with the configured strategy. Failures
+    /// only warn, so the network never keeps the repository from opening.
+    /// Returns whether it was updated, so the post update command should run.
+    async fn update_existing(&self, repo: &Repository, path: &Path, shell: bool) -> bool {
+        let updater = self.app.updater();
+        if !updater.is_due(path).await {
+            return false;
+        }
+
+        let label = repo.to_rel_path().display().to_string();
+        let result = if std::io::stdout().is_terminal() && shell {
+            let path = path.to_path_buf();
+            InlineCommand::new(format!("updating: {label}"))
+                .with_theme(self.app.theme())
+                .execute(move || async move { updater.update(&path).await })
+                .await
+        } else {
+            eprintln!("updating repository...");
+            updater.update(path).await
+        };
+
+        match result {
+            Ok(()) => {
+                self.app
+                    .updater()
+                    .warn_about_local_changes(path, &label)
+                    .await;
+                true
+            }
+            Err(e) => {
+                let e = format!("{e:#}");
+                let reason = e.lines().next().unwrap_or_default();
+                eprintln!("warning: failed to update {label}: {reason}");
+                false
+            }
+        }
+    }
 }
 
 pub trait RepositoryMatcher {
@@ -132,15 +238,244 @@ pub trait RepositoryMatcher {
 
 impl RepositoryMatcher for FuzzyMatcher {
     fn match_repositories(&self, pattern: &str, repositories: &[Repository]) -> Vec<Repository> {
-        let haystack: BTreeMap<String, &Repository> = repositories
-            .iter()
-            .map(|r| (r.to_rel_path().display().to_string(), r))
-            .collect();
-        let keys: Vec<&str> = haystack.keys().map(|s| s.as_str()).collect();
-
-        self.match_pattern(pattern, &keys)
+        self.match_items(pattern, repositories)
             .into_iter()
-            .filter_map(|key| haystack.get(key).map(|r| (*r).to_owned()))
+            .map(|(repo, _)| repo.to_owned())
             .collect()
     }
 }
+
+/// Outcome of resolving a search against the scored matches.
+#[derive(Debug, PartialEq)]
+enum MatchOutcome<'a> {
+    Selected(&'a Repository),
+    Ambiguous(Vec<(&'a Repository, u32)>),
+    NotFound,
+}
+
+/// Picks a repository from matches sorted best first. A single exact match
+/// always wins; otherwise the best match is ambiguous when the runner-up
+/// scores within `threshold` points of it.
+fn resolve_match<'a>(
+    matches: &[(&'a Repository, u32)],
+    text: &str,
+    exact_only: bool,
+    threshold: u32,
+) -> MatchOutcome<'a> {
+    let exact: Vec<(&Repository, u32)> = matches
+        .iter()
+        .filter(|(repo, _)| is_exact_match(repo, text))
+        .copied()
+        .collect();
+
+    match exact.as_slice() {
+        [(repo, _)] => return MatchOutcome::Selected(repo),
+        [_, _, ..] => return MatchOutcome::Ambiguous(exact),
+        [] if exact_only => return MatchOutcome::NotFound,
+        [] => {}
+    }
+
+    let Some((best, best_score)) = matches.first() else {
+        return MatchOutcome::NotFound;
+    };
+
+    let close: Vec<(&Repository, u32)> = matches
+        .iter()
+        .take_while(|(_, score)| best_score - score <= threshold)
+        .copied()
+        .collect();
+
+    if close.len() > 1 {
+        MatchOutcome::Ambiguous(close)
+    } else {
+        MatchOutcome::Selected(best)
+    }
+}
+
+fn is_exact_match(repo: &Repository, text: &str) -> bool {
+    let text = text.trim().to_lowercase();
+    let owner_repo = format!("{}/{}", repo.owner, repo.repo_name).to_lowercase();
+    let rel_path = repo.to_rel_path().display().to_string().to_lowercase();
+
+    repo.repo_name.to_lowercase() == text || owner_repo == text || rel_path == text
+}
+
+/// With `--exact`, several exact matches never silently open the first:
+/// they fail, or open the picker when that's configured.
+fn ambiguity_action(configured: AmbiguityAction, exact: bool) -> AmbiguityAction {
+    match configured {
+        AmbiguityAction::First if exact => AmbiguityAction::Fail,
+        action => action,
+    }
+}
+
+/// Resolves a non-interactive search to a single repository, honouring
+/// `--exact`, `--list` and the configured ambiguity handling. Returns
+/// `None` when the matches were only listed.
+pub fn select_repository(
+    app: &'static App,
+    needle: &str,
+    repositories: &[Repository],
+    matching: &MatchArgs,
+) -> anyhow::Result<Option<Repository>> {
+    let mut matches = app.fuzzy_matcher().match_items(needle, repositories);
+    let text = Query::parse(needle).text;
+    if matching.exact {
+        matches.retain(|(repo, _)| is_exact_match(repo, &text));
+    }
+
+    if matching.list {
+        for (repo, score) in &matches {
+            println!("{score:>6}  {}", repo.to_rel_path().display());
+        }
+        return Ok(None);
+    }
+
+    let search = app.config.settings.search.as_ref();
+    let threshold = search
+        .and_then(|s| s.ambiguity_threshold)
+        .unwrap_or(DEFAULT_AMBIGUITY_THRESHOLD);
+    let action = ambiguity_action(
+        matching
+            .on_ambiguous
+            .or(search.and_then(|s| s.on_ambiguous))
+            .unwrap_or_default(),
+        matching.exact,
+    );
+
+    let candidates = match resolve_match(&matches, &text, matching.exact, threshold) {
+        MatchOutcome::Selected(repo) => return Ok(Some(repo.to_owned())),
+        MatchOutcome::NotFound if matching.exact => {
+            anyhow::bail!("no repository exactly matching '{}' found", needle)
+        }
+        MatchOutcome::NotFound => anyhow::bail!("failed to find repository"),
+        MatchOutcome::Ambiguous(candidates) => candidates,
+    };
+
+    match action {
+        AmbiguityAction::First => Ok(Some(candidates[0].0.to_owned())),
+        AmbiguityAction::Interactive if std::io::stdin().is_terminal() => {
+            let candidates: Vec<Repository> =
+                candidates.into_iter().map(|(r, _)| r.to_owned()).collect();
+            let repo = app
+                .interactive()
+                .interactive_search(&candidates)?
+                .ok_or(anyhow::anyhow!("failed to find a repository"))?;
+            Ok(Some(repo))
+        }
+        AmbiguityAction::Fail | AmbiguityAction::Interactive => {
+            let listing = candidates
+                .iter()
+                .map(|(repo, score)| format!("  {score:>6}  {}", repo.to_rel_path().display()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if matching.exact {
+                anyhow::bail!(
+                    "'{}' exactly matches several repositories, use the full provider/owner/repo path:\n{}",
+                    needle,
+                    listing
+                )
+            }
+            anyhow::bail!(
+                "'{}' is ambiguous, refine the search or use --exact:\n{}",
+                needle,
+                listing
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(owner: &str, name: &str) -> Repository {
+        Repository {
+            provider: "github.com".into(),
+            owner: owner.into(),
+            repo_name: name.into(),
+            ssh_url: format!("ssh://git@github.com/{owner}/{name}.git"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exact_name_match_wins_over_close_scores() {
+        let api = repo("org", "api");
+        let api_gateway = repo("org", "api-gateway");
+        let matches = vec![(&api_gateway, 100), (&api, 99)];
+
+        assert_eq!(
+            resolve_match(&matches, "api", false, 8),
+            MatchOutcome::Selected(&api)
+        );
+    }
+
+    #[test]
+    fn close_scores_are_ambiguous() {
+        let a = repo("org", "service-a");
+        let b = repo("org", "service-b");
+        let c = repo("org", "other");
+        let matches = vec![(&a, 100), (&b, 95), (&c, 50)];
+
+        assert_eq!(
+            resolve_match(&matches, "serv", false, 8),
+            MatchOutcome::Ambiguous(vec![(&a, 100), (&b, 95)])
+        );
+    }
+
+    #[test]
+    fn clear_winner_is_selected() {
+        let a = repo("org", "service-a");
+        let b = repo("org", "service-b");
+        let matches = vec![(&a, 100), (&b, 80)];
+
+        assert_eq!(
+            resolve_match(&matches, "serv", false, 8),
+            MatchOutcome::Selected(&a)
+        );
+    }
+
+    #[test]
+    fn exact_only_requires_an_exact_match() {
+        let a = repo("org", "service-a");
+        let matches = vec![(&a, 100)];
+
+        assert_eq!(
+            resolve_match(&matches, "service", true, 8),
+            MatchOutcome::NotFound
+        );
+        assert_eq!(
+            resolve_match(&matches, "org/service-a", true, 8),
+            MatchOutcome::Selected(&a)
+        );
+    }
+
+    #[test]
+    fn same_name_in_multiple_owners_is_ambiguous() {
+        let a = repo("org", "api");
+        let b = repo("other", "api");
+        let matches = vec![(&a, 100), (&b, 100)];
+
+        assert_eq!(
+            resolve_match(&matches, "api", true, 8),
+            MatchOutcome::Ambiguous(vec![(&a, 100), (&b, 100)])
+        );
+    }
+
+    #[test]
+    fn exact_never_picks_the_first_of_several() {
+        assert_eq!(
+            ambiguity_action(AmbiguityAction::First, true),
+            AmbiguityAction::Fail
+        );
+        assert_eq!(
+            ambiguity_action(AmbiguityAction::Interactive, true),
+            AmbiguityAction::Interactive
+        );
+        assert_eq!(
+            ambiguity_action(AmbiguityAction::First, false),
+            AmbiguityAction::First
+        );
+    }
+}
@@ -22,14 +22,23 @@ management, and scratch-pad project workspaces.
 
 ```
 gitnow [OPTIONS] [SEARCH]            # search/clone/open a repository
-gitnow update                        # refresh the local repository cache
+gitnow update [--json]               # refresh the cache and report changes
 gitnow clone --search <REGEX>        # batch-clone repositories matching a pattern
+gitnow clone --interactive           # pick repositories to batch-clone
 gitnow worktree [SEARCH] [OPTIONS]   # create and enter a git worktree for a branch
+gitnow mirrors refresh|prune         # manage the local clone mirrors
+gitnow migrate-layout [--dry-run]    # move clones to the current path_template
+gitnow status [--search REGEX]       # list clones with uncommitted or unpushed work
+gitnow sync [--pull]                 # fetch or fast-forward every local clone
+gitnow exec --search <REGEX> -- CMD  # run a command in every matching local clone
+gitnow prune [--dry-run] [--force]   # delete clones gone or archived upstream, or unused
 gitnow project [SEARCH] [OPTIONS]    # open an existing scratch-pad project
 gitnow project create [NAME]         # create a new multi-repo project
 gitnow project add [NAME]            # add repositories to a project
 gitnow project delete [NAME]         # delete a project
 gitnow project list [OPTIONS]        # list all projects and their repos
+gitnow recent [OPTIONS]              # pick a recently opened location
+gitnow last                          # jump back to the previous location
 gitnow init zsh                      # print zsh shell integration script
 gitnow skill                         # print this reference (you are here)
 ```
@@ -44,9 +53,11 @@ gitnow [OPTIONS] [SEARCH]
 
 Search for a repository, optionally clone it, and open a shell inside it.
 
-- If SEARCH is provided, fuzzy-matches against cached repositories.
+- If SEARCH is provided, fuzzy-matches against cached repositories (see
+  "Search syntax" below).
 - If omitted, opens an interactive fuzzy-search picker.
-- Clones the repository if it does not exist locally.
+- Clones the repository if it does not exist locally, or updates it with
+  `[settings.update]` if it does.
 - Spawns a sub-shell in the repository directory.
 
 **Flags:**
@@ -57,7 +68,11 @@ Search for a repository, optionally clone it, and open a shell inside it.
 | `--no-shell`          | Print the path instead of spawning a shell               |
 | `--force-refresh`     | Force a fresh clone even if the repo already exists      |
 | `--force-cache-update`| Update the cache before searching                        |
+| `--profile NAME`      | Clone with this entry of `settings.clone_profiles`       |
 | `--chooser-file PATH` | Write selected path to this file (implies --no-shell)   |
+| `--exact`             | Only accept an exact name, owner/name or path match      |
+| `--list`              | Print all matches with scores instead of opening one     |
+| `--on-ambiguous MODE` | `first`, `fail` or `interactive` when top matches are close |
 | `-c, --config PATH`  | Path to config file (global flag)                        |
 
 **Environment variables:**
@@ -71,19 +86,134 @@ Search for a repository, optionally clone it, and open a shell inside it.
 Fetch all repositories from configured providers and update the local cache.
 Should be run periodically or after adding new providers/organisations.
 
-No flags.
+Prints the repositories that were added, removed or renamed since the previous
+cache. Renames and transfers are detected by the provider's repository id. A
+warning is printed for removed repositories that still have a local clone.
+
+| Flag     | Description                                                  |
+|----------|--------------------------------------------------------------|
+| `--json` | Output `{added, removed, renamed}` as JSON with each repository's provider `id`; removed entries include `local_clone` (path or null) |
+
+---
+
+### `gitnow clone --search <REGEX>` / `gitnow clone --interactive`
+
+Batch-clone all repositories whose relative path matches the given regex, or
+those picked in a multi-select picker. Clones up to 5 repositories concurrently
+with a live progress list, then prints a summary. Skips repos that already
+exist locally. Exits non-zero if any clone failed.
+
+**Flags (one is required):**
+| Flag                  | Description                                          |
+|-----------------------|------------------------------------------------------|
+| `--search <REGEX>`    | Regular expression to match repository paths; with `--interactive` it narrows the picker |
+| `-i`, `--interactive` | Pick the repositories to clone (Tab toggles, Enter confirms) |
+| `--profile NAME`      | Clone with this entry of `settings.clone_profiles`   |
+
+---
+
+### `gitnow mirrors refresh` / `gitnow mirrors prune`
+
+With `settings.mirrors.enabled`, every clone first creates a `git clone --mirror`
+of the repository under `<cache location>/mirrors` (once), then clones with
+`--reference-if-able <mirror> --dissociate`. Clones never depend on the mirror.
+Clone profiles with `depth` or `filter` skip the mirror.
+
+| Command / flag                    | Description                                          |
+|-----------------------------------|------------------------------------------------------|
+| `mirrors refresh [--search REGEX]`| Fetch into every mirror (or those matching the regex) concurrently |
+| `mirrors prune`                   | Delete mirrors of repositories no provider lists anymore |
+| `mirrors prune --all`             | Delete every mirror                                  |
+| `mirrors prune --dry-run`         | Print the mirrors that would be deleted              |
+
+---
+
+### `gitnow migrate-layout`
+
+Moves existing clones from an old path layout to the one rendered by the
+current `path_template` settings. Only directories that are clones (with a
+`.git` or `.bare`) move; clones whose new path is taken are skipped. Worktrees
+of bare clones are repaired after the move, and emptied directories removed.
+
+| Flag               | Description                                              |
+|--------------------|----------------------------------------------------------|
+| `--from TEMPLATE`  | Template of the current layout (default: `{{ provider }}/{{ owner }}/{{ repo }}`) |
+| `--dry-run`        | Print the moves without making them                      |
+
+---
+
+### `gitnow status`
+
+Checks every clone, worktree of a bare clone and project repository
+concurrently, and lists those with uncommitted changes, unpushed commits on
+any local branch, stashes or a detached HEAD. Exits non-zero when any of them
+need attention.
+Locations are `provider/owner/repo`, `provider/owner/repo@<worktree>` and
+`<project>/<repo>`.
+
+| Flag               | Description                                              |
+|--------------------|----------------------------------------------------------|
+| `--search REGEX`   | Only check locations matching the regex                  |
+| `--all`            | Also list locations that don't need attention            |
+| `--json`           | Output as JSON                                           |
+| `--no-cache`       | Skip the cache                                           |
 
 ---
 
-### `gitnow clone --search <REGEX>`
+### `gitnow sync`
 
-Batch-clone all repositories whose relative path matches the given regex.
-Clones up to 5 repositories concurrently. Skips repos that already exist locally.
+Fetches every clone and project repository, and the `.bare` repository of
+worktree clones, concurrently with a live progress list. Checkouts with
+uncommitted changes are skipped. Bare repositories fetch into
+`refs/remotes/origin/*`, then create local branches for new upstream ones and
+fast-forward the local branches no worktree has checked out. Prints what was synced, skipped and failed; exits
+non-zero if anything failed.
 
-**Required flags:**
-| Flag               | Description                                |
-|--------------------|--------------------------------------------|
-| `--search <REGEX>` | Regular expression to match repository paths |
+| Flag               | Description                                              |
+|--------------------|----------------------------------------------------------|
+| `--search REGEX`   | Only sync locations matching the regex                   |
+| `--pull`           | Also fast-forward branches that are behind and haven't diverged |
+| `--no-cache`       | Skip the cache                                           |
+
+---
+
+### `gitnow exec --search <REGEX> -- <COMMAND>`
+
+Runs a command in every clone, worktree and project repository matching the
+search, concurrently. A single argument after `--` runs as a `$SHELL -c`
+script; several run as a program and its arguments. Lists the locations where
+the command failed and exits non-zero if there were any.
+
+| Flag               | Description                                              |
+|--------------------|----------------------------------------------------------|
+| `--search REGEX`   | Regex matched against `provider/owner/repo`, `provider/owner/repo@<worktree>` or `<project>/<repo>` |
+| `--query QUERY`    | Search syntax with qualifiers, matched against each location's repository |
+| `--jobs N` / `-j`  | Locations the command runs in at once (default: 4)       |
+| `--group`          | Print each location's output as one block instead of prefixing every line |
+| `--clone`          | Clone matching repositories that aren't cloned yet first, after listing them and confirming |
+| `--force` / `-f`   | With `--clone`, clone without confirming                 |
+| `--no-cache`       | Skip the cache                                           |
+
+Example: `gitnow exec --query "owner:kjuulh lang:rust" -- cargo update -p serde`
+
+---
+
+### `gitnow prune`
+
+Flags clones under `projects.directory` that were deleted or archived
+upstream, or with `--older-than` that weren't used for that many days. A clone
+no provider lists is only deleted upstream when its `origin` belongs to a
+configured owner and `git ls-remote` says it doesn't exist; otherwise it's
+listed as unknown and `--force` leaves it alone. Only clones without uncommitted changes, stashes or commits missing from
+every remote can be deleted; the rest are listed with the reason they're kept.
+Without `--force`, pick the clones in a multi-select picker and confirm.
+
+| Flag               | Description                                              |
+|--------------------|----------------------------------------------------------|
+| `--older-than N`   | Also flag clones unused for N days (git activity or opened with gitnow) |
+| `--dry-run`        | Print the clones that would be deleted                   |
+| `--force`          | Delete every prunable clone, except unknown ones, without picking or confirming |
+| `--no-cache`       | Skip the cache                                           |
 
 ---
 
@@ -107,6 +237,7 @@ working on multiple branches simultaneously without switching.
 | `-b, --branch`    | Branch name (skips interactive branch picker)   |
 | `--no-cache`      | Skip the local cache                           |
 | `--no-shell`      | Print path instead of spawning a shell         |
+| `--exact`, `--list`, `--on-ambiguous` | Same as for the default command |
 
 ---
 
@@ -137,6 +268,7 @@ and optionally apply a template.
 | `--no-template`     | Skip template selection entirely                     |
 | `--no-cache`        | Skip local cache when listing repos                  |
 | `--no-shell`        | Print path instead of spawning a shell               |
+| `--profile NAME`    | Clone with this entry of `settings.clone_profiles`   |
 
 Templates live in `~/.gitnow/templates/` (or the configured directory). Each
 subdirectory is a template; its contents are copied into the new project.
@@ -185,6 +317,32 @@ deletion unless `--quiet` is set.
 
 ---
 
+### `gitnow recent [OPTIONS]`
+
+Pick a recently opened repository, worktree or project from the history and
+enter it. Only locations that still exist on disk are listed, newest first.
+
+| Flag              | Description                                      |
+|-------------------|--------------------------------------------------|
+| `-n, --limit N`   | Maximum number of entries (default: 50)          |
+| `--json`          | Print `[{kind, name, path, opened_at}]` instead of opening a picker |
+| `--no-shell`      | Print path instead of spawning a shell           |
+
+---
+
+### `gitnow last`
+
+Jump back to the most recently opened location that is not the current
+directory, like `cd -`. Uses the chooser file when set.
+
+| Flag         | Description                              |
+|--------------|------------------------------------------|
+| `--no-shell` | Print path instead of spawning a shell   |
+
+History is stored in `<cache>/history.json` and also drives frecency ranking.
+
+---
+
 ### `gitnow init zsh`
 
 Print a zsh shell integration script to stdout. Typically used as:
@@ -204,6 +362,28 @@ Print this LLM-readable reference document to stdout.
 
 ---
 
+## Search syntax
+
+Applies to `gitnow [SEARCH]`, the interactive pickers, `worktree [SEARCH]` and
+`project create/add --repos`. Whitespace-separated tokens are either field
+qualifiers, which filter before fuzzy matching, or fuzzy text.
+
+| Token                 | Meaning                                           |
+|-----------------------|---------------------------------------------------|
+| `owner:NAME`          | Owner/organisation equals NAME                    |
+| `provider:HOST`       | Provider host equals HOST (`github` matches `github.com`) |
+| `lang:LANG`           | Primary language equals LANG                      |
+| `topic:TOPIC`         | Repository has TOPIC                              |
+| `is:cloned`           | Repository exists locally                         |
+| `is:archived`         | Repository is archived upstream                   |
+| `'text`               | Exact substring                                   |
+| `^text` / `text$`     | Anchored to start / end of `provider/owner/repo`  |
+| `!text`               | Exclude matches                                   |
+
+Qualifier values are case-insensitive. Unknown qualifiers are matched as text.
+
+---
+
 ## Configuration
 
 Config file location (in priority order):
@@ -215,21 +395,41 @@ Config file location (in priority order):
 
 ```toml
 [settings]
-# Where repositories are cloned to (default: ~/git)
-projects = { directory = "~/git" }
+# Where repositories are cloned to (default: ~/git), and the path of each
+# repository under it. Variables: {{ provider }}, {{ owner }}, {{ repo }}
+# Default path_template: "{{ provider }}/{{ owner }}/{{ repo }}"
+projects = { directory = "~/git", path_template = "{{ provider }}/{{ owner }}/{{ repo }}" }
 
 # Custom clone command (minijinja template)
-# Available variables: {{ ssh_url }}, {{ path }}
-# Default: "git clone {{ ssh_url }} {{ path }}"
-clone_command = "git clone {{ ssh_url }} {{ path }}"
-
-# Commands to run after cloning a repository
-post_clone_command = "echo 'cloned!'"
-# or as a list:
-# post_clone_command = ["cmd1", "cmd2"]
-
-# Commands to run when opening an already-cloned repository
-post_update_command = "git fetch --prune"
+# Available variables: {{ ssh_url }}, {{ path }}, {{ clone_args }}, with a
+# clone profile {{ profile }}, {{ depth }}, {{ filter }}, {{ sparse_paths }},
+# and with mirrors enabled {{ mirror_path }}
+# Default: "git clone --progress {{ clone_args }} {{ ssh_url }} {{ path }}"
+clone_command = "git clone --progress {{ clone_args }} {{ ssh_url }} {{ path }}"
+
+# Commands to run after cloning a repository, with $SHELL -c in the clone.
+# Minijinja templates with {{ path }}, {{ repo }}, {{ owner }}, {{ provider }}
+# and {{ ssh_url }}, inserted as they are: shell-quote them with the quote
+# filter, e.g. {{ path | quote }}, or on a list each item.
+# Failures are reported and the next command still runs.
+post_clone_command = "echo cloned {{ repo | quote }}"
+# or as a list, where each entry can be a table with options:
+# post_clone_command = [
+#   "cmd1 | cmd2",
+#   { command = "mise install", fail_on_error = true, timeout = 300 },
+# ]
+
+# Commands to run after [settings.update] updated an already-cloned
+# repository when opening it
+post_update_command = "git submodule update --init"
+
+# Other hook points, with the same format and variables:
+# pre_clone_command runs before a clone, in its parent directory.
+# on_enter_command runs before entering a repository, worktree or project
+# (also with --no-shell/the shell integration), with {{ kind }} set to
+# repository, worktree or project, and {{ project }} for projects.
+pre_clone_command = "echo cloning {{ repo | quote }}"
+on_enter_command = "direnv allow"
 
 [settings.cache]
 # Where the cache is stored (default: ~/.cache/gitnow)
@@ -238,11 +438,60 @@ location = "~/.cache/gitnow"
 # Cache duration (default: 7 days). Set to false to disable.
 duration = { days = 7, hours = 0, minutes = 0 }
 
+[settings.search]
+# Boost recently/frequently opened repositories in search results
+# (default: 1.0, 0 disables). History is stored in <cache>/history.json.
+frecency_weight = 1.0
+# Non-interactive search with close top matches: "first" (default), "fail"
+# or "interactive". A single exact name match always wins; with --exact,
+# several exact matches fail unless this is "interactive".
+on_ambiguous = "first"
+# Runner-up within this many points of the best score is ambiguous (default: 8)
+ambiguity_threshold = 8
+
 [settings.worktree]
 # Custom worktree commands (minijinja templates)
-clone_command = "git clone --bare {{ ssh_url }} {{ bare_path }}"
+clone_command = "git clone --bare --progress {{ clone_args }} {{ ssh_url }} {{ bare_path }}"
 add_command = "git -C {{ bare_path }} worktree add {{ worktree_path }} {{ branch }}"
 list_branches_command = "git -C {{ bare_path }} branch -r --format=%(refname:short)"
+# Hook run in a newly added worktree; adds {{ branch }}, {{ bare_path }} and
+# {{ worktree_path }} to the repository variables
+post_add_command = "mise install"
+
+# Clone profiles for large repositories. Used with --profile NAME, or for
+# repositories matching `repositories` (globs on provider/owner/repo; `*`
+# stays within a segment, `**` crosses them). The first match wins.
+[[settings.clone_profiles]]
+name = "monorepo"
+repositories = ["github.com/bigcorp/monorepo"]
+depth = 1                          # --depth
+filter = "blob:none"               # --filter
+sparse_paths = ["services/api"]    # git sparse-checkout set (not for worktrees)
+
+# Per-repository overrides of clone_command, pre_clone_command,
+# post_clone_command, post_update_command, on_enter_command and
+# [settings.worktree]. Matched against
+# provider/owner/repo with globs or `regex:` patterns; the first matching
+# rule wins and unset fields fall back to [settings].
+[[settings.rules]]
+repositories = ["github.com/lunarway/*"]
+clone_command = "jj git clone {{ ssh_url }} {{ path }}"
+worktree = { clone_command = "jj git clone {{ ssh_url }} {{ bare_path }}" }
+
+[settings.update]
+# Update an already-cloned repository when opening it: "none" (default),
+# "fetch", or "pull" (fetch, then fast-forward a clean, non-diverged branch).
+# Failures and timeouts only warn; the repository opens regardless.
+# post_update_command runs after each successful update only.
+strategy = "fetch"
+interval_hours = 4                 # only when the last fetch is older
+timeout = 10                       # seconds (default: 10)
+
+[settings.mirrors]
+# Clone via a local mirror per repository (default: false)
+enabled = true
+# Where mirrors are stored (default: <cache location>/mirrors)
+directory = "~/.cache/gitnow/mirrors"
 
 [settings.project]
 # Where scratch-pad projects are stored (default: ~/.gitnow/projects)
@@ -251,6 +500,16 @@ directory = "~/.gitnow/projects"
 templates_directory = "~/.gitnow/templates"
 # Automatically delete old projects before project commands (optional)
 auto_delete_older_than_days = 30
+# Project hooks, with {{ project }}, {{ path }} and {{ repos }} (a list of
+# provider/owner/repo). pre_create and post_delete run in the projects
+# directory, the others in the project. A pre_ hook failing with
+# fail_on_error stops the change.
+pre_create_command = "echo creating {{ project | quote }}"
+post_create_command = "echo {{ repos | quote }} > repos.txt"
+post_add_command = "echo added {{ repos | quote }}"
+post_remove_command = "echo removed {{ repos | quote }}"
+pre_delete_command = "tar czf /tmp/{{ project | quote }}.tgz ."
+post_delete_command = "echo deleted {{ project | quote }}"
 
 # --- Providers ---
 
@@ -260,6 +519,7 @@ current_user = "your-username"     # optional, for user-specific repos
 users = ["user1"]                  # fetch repos for these users
 organisations = ["org1", "org2"]   # fetch repos for these orgs
 url = "https://api.github.com"     # optional, for GitHub Enterprise
+path_template = "{{ owner }}/{{ repo }}"  # optional, overrides the global one
 
 [[providers.gitea]]
 url = "https://gitea.example.com/api/v1"
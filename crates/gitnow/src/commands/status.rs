@@ -0,0 +1,219 @@
+use futures::{StreamExt, stream};
+use regex::Regex;
+
+use crate::{
+    app::App,
+    cache::load_repositories,
+    git_repo::{GitRepo, RepoStatus},
+    local_clones::{LocalClone, find_local_clones},
+};
+
+/// Checkouts inspected at once.
+const STATUS_CONCURRENCY: usize = 16;
+
+#[derive(clap::Parser)]
+pub struct StatusCommand {
+    /// Regex matched against each location, e.g. `provider/owner/repo`,
+    /// `provider/owner/repo@worktree` or `project/repo`
+    #[arg(long = "search")]
+    search: Option<String>,
+
+    /// Also list checkouts that don't need attention
+    #[arg(long = "all", default_value = "false")]
+    all: bool,
+
+    /// Output as JSON
+    #[arg(long = "json", default_value = "false")]
+    json: bool,
+
+    /// Skip cache
+    #[arg(long = "no-cache", default_value = "false")]
+    no_cache: bool,
+}
+
+/// The status of a checkout, and the commits on any of its local branches
+/// that no remote has, including branches that were never pushed or aren't
+/// checked out.
+struct CheckoutStatus {
+    status: RepoStatus,
+    unpushed: u32,
+}
+
+impl CheckoutStatus {
+    async fn load(repo: GitRepo) -> anyhow::Result<Self> {
+        Ok(Self {
+            status: repo.status().await?,
+            unpushed: repo.unpushed_commits().await?,
+        })
+    }
+}
+
+/// Uncommitted changes, unpushed commits, stashes or a detached HEAD.
+fn needs_attention(checkout: &CheckoutStatus) -> bool {
+    let status = &checkout.status;
+    status.is_dirty()
+        || status.ahead > 0
+        || checkout.unpushed > 0
+        || status.stashes > 0
+        || status.branch.is_none()
+}
+
+impl StatusCommand {
+    pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        let repositories = load_repositories(app, !self.no_cache).await?;
+        let mut clones = find_local_clones(app, &repositories);
+        if let Some(search) = &self.search {
+            let search = Regex::new(search)?;
+            clones.retain(|clone| search.is_match(&clone.name));
+        }
+
+        let results: Vec<(LocalClone, anyhow::Result<CheckoutStatus>)> = stream::iter(clones)
+            .map(|clone| async move {
+                let status = CheckoutStatus::load(GitRepo::new(&clone.path)).await;
+                (clone, status)
+            })
+            .buffered(STATUS_CONCURRENCY)
+            .collect()
+            .await;
+
+        let attention = results
+            .iter()
+            .filter(|(_, status)| status.as_ref().map_or(true, needs_attention))
+            .count();
+        let shown: Vec<_> = results
+            .iter()
+            .filter(|(_, status)| self.all || status.as_ref().map_or(true, needs_attention))
+            .collect();
+
+        if self.json {
+            print_json(&shown)?;
+        } else {
+            print_table(&shown);
+        }
+
+        if attention > 0 {
+            anyhow::bail!(
+                "{attention} of {} checkouts need attention",
+                results.len()
+            );
+        }
+        if !self.json {
+            eprintln!("all {} checkouts are clean", results.len());
+        }
+
+        Ok(())
+    }
+}
+
+fn print_json(results: &[&(LocalClone, anyhow::Result<CheckoutStatus>)]) -> anyhow::Result<()> {
+    let entries: Vec<serde_json::Value> = results
+        .iter()
+        .map(|(clone, status)| match status {
+            Ok(CheckoutStatus { status, unpushed }) => serde_json::json!({
+                "kind": clone.kind,
+                "name": clone.name,
+                "path": clone.path,
+                "branch": status.branch,
+                "upstream": status.upstream,
+                "changes": status.changes,
+                "ahead": status.ahead,
+                "behind": status.behind,
+                "unpushed": unpushed,
+                "stashes": status.stashes,
+                "detached": status.branch.is_none(),
+            }),
+            Err(e) => serde_json::json!({
+                "kind": clone.kind,
+                "name": clone.name,
+                "path": clone.path,
+                "error": format!("{e:#}"),
+            }),
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+
+    Ok(())
+}
+
+/// Prints a table of the checkouts, with those that failed below it.
+fn print_table(results: &[&(LocalClone, anyhow::Result<CheckoutStatus>)]) {
+    let rows: Vec<[String; 7]> = results
+        .iter()
+        .filter_map(|(clone, status)| {
+            let CheckoutStatus { status, unpushed } = status.as_ref().ok()?;
+            Some([
+                clone.name.clone(),
+                status
+                    .branch
+                    .clone()
+                    .unwrap_or_else(|| "(detached)".into()),
+                status.changes.to_string(),
+                status.ahead.to_string(),
+                status.behind.to_string(),
+                unpushed.to_string(),
+                status.stashes.to_string(),
+            ])
+        })
+        .collect();
+    let failed = results
+        .iter()
+        .filter_map(|(clone, status)| Some((clone, status.as_ref().err()?)));
+
+    let header = [
+        "LOCATION", "BRANCH", "CHANGES", "AHEAD", "BEHIND", "UNPUSHED", "STASHES",
+    ];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |row: [&str; 7]| {
+        let [location, branch, counts @ ..] = row;
+        let mut line = format!("{location:<w0$}  {branch:<w1$}", w0 = widths[0], w1 = widths[1]);
+        for (count, width) in counts.iter().zip(&widths[2..]) {
+            line.push_str(&format!("  {count:>width$}"));
+        }
+        line.trim_end().to_string()
+    };
+
+    if !rows.is_empty() {
+        println!("{}", format_row(header));
+    }
+    for row in &rows {
+        println!("{}", format_row(row.each_ref().map(String::as_str)));
+    }
+    for (clone, e) in failed {
+        let e = format!("{e:#}");
+        eprintln!("  ✗ {}: {}", clone.name, e.lines().next().unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_attention_for_local_work_or_detached_head() {
+        let clean = RepoStatus {
+            branch: Some("main".into()),
+            upstream: Some("origin/main".into()),
+            ..Default::default()
+        };
+        let cases = [
+            ("clean", clean.clone(), 0, false),
+            ("behind", RepoStatus { behind: 2, ..clean.clone() }, 0, false),
+            ("dirty", RepoStatus { changes: 1, ..clean.clone() }, 0, true),
+            ("ahead", RepoStatus { ahead: 1, ..clean.clone() }, 0, true),
+            ("unpushed", RepoStatus { upstream: None, ..clean.clone() }, 3, true),
+            ("stash", RepoStatus { stashes: 1, ..clean.clone() }, 0, true),
+            ("detached", RepoStatus { branch: None, ..clean.clone() }, 0, true),
+        ];
+
+        for (name, status, unpushed, expected) in cases {
+            let checkout = CheckoutStatus { status, unpushed };
+            assert_eq!(needs_attention(&checkout), expected, "{name}");
+        }
+    }
+}
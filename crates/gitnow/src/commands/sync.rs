@@ -0,0 +1,236 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use futures::{StreamExt, stream};
+use regex::Regex;
+
+use crate::{
+    app::App,
+    cache::load_repositories,
+    components::task_list::{TaskList, TaskState},
+    git_repo::{GitRepo, RepoStatus},
+    history::HistoryKind,
+    layout::PathLayoutApp,
+    local_clones::find_local_clones,
+    theme::ThemeApp,
+};
+
+/// Repositories synced at once.
+const SYNC_CONCURRENCY: usize = 8;
+
+#[derive(clap::Parser)]
+pub struct SyncCommand {
+    /// Regex matched against each location, e.g. `provider/owner/repo` or
+    /// `project/repo`
+    #[arg(long = "search")]
+    search: Option<String>,
+
+    /// Also fast-forward checked out branches that are behind their upstream
+    #[arg(long = "pull", default_value = "false")]
+    pull: bool,
+
+    /// Skip cache
+    #[arg(long = "no-cache", default_value = "false")]
+    no_cache: bool,
+}
+
+/// A checkout, or the `.bare` repository shared by the worktrees of a
+/// repository.
+struct SyncTarget {
+    name: String,
+    path: PathBuf,
+    bare: bool,
+}
+
+impl SyncCommand {
+    pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        let repositories = load_repositories(app, !self.no_cache).await?;
+        let layout = app.layout();
+
+        let mut targets: Vec<SyncTarget> = find_local_clones(app, &repositories)
+            .into_iter()
+            // Worktrees are synced through their bare repository.
+            .filter(|clone| clone.kind != HistoryKind::Worktree)
+            .map(|clone| SyncTarget {
+                name: clone.name,
+                path: clone.path,
+                bare: false,
+            })
+            .collect();
+        for repository in &repositories {
+            let path = layout.path(repository).join(".bare");
+            if path.is_dir() {
+                targets.push(SyncTarget {
+                    name: repository.to_rel_path().display().to_string(),
+                    path,
+                    bare: true,
+                });
+            }
+        }
+        targets.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if let Some(search) = &self.search {
+            let search = Regex::new(search)?;
+            targets.retain(|target| search.is_match(&target.name));
+        }
+        if targets.is_empty() {
+            eprintln!("no local clones to sync");
+            return Ok(());
+        }
+
+        // Checkouts with uncommitted changes are left alone entirely.
+        let dirty: Vec<bool> = stream::iter(&targets)
+            .map(|target| async move {
+                !target.bare
+                    && GitRepo::new(&target.path)
+                        .status()
+                        .await
+                        .is_ok_and(|status| status.is_dirty())
+            })
+            .buffered(SYNC_CONCURRENCY)
+            .collect()
+            .await;
+        let (dirty, targets): (Vec<_>, Vec<_>) = targets
+            .into_iter()
+            .zip(dirty)
+            .partition(|(_, dirty)| *dirty);
+        let mut skipped: Vec<String> = dirty
+            .into_iter()
+            .map(|(target, _)| format!("{}: uncommitted changes", target.name))
+            .collect();
+        let targets: Vec<SyncTarget> = targets.into_iter().map(|(target, _)| target).collect();
+        let total = targets.len() + skipped.len();
+
+        // Why each pulled checkout wasn't fast-forwarded, if it wasn't.
+        let not_pulled = Arc::new(Mutex::new(vec![None; targets.len()]));
+        let states = if targets.is_empty() {
+            Vec::new()
+        } else {
+            let labels = targets.iter().map(|target| target.name.clone()).collect();
+            TaskList::new(labels)
+                .with_theme(app.theme())
+                .execute(SYNC_CONCURRENCY, |i| {
+                    let repo = GitRepo::new(&targets[i].path);
+                    let bare = targets[i].bare;
+                    let pull = self.pull;
+                    let not_pulled = Arc::clone(&not_pulled);
+                    let sync = async move {
+                        if bare {
+                            return repo.fetch_bare().await;
+                        }
+
+                        repo.fetch().await?;
+                        if pull {
+                            let status = repo.fast_forward().await?;
+                            not_pulled.lock().unwrap()[i] = not_fast_forwarded(&status);
+                        }
+                        Ok(())
+                    };
+                    // Only git's first line of stderr, so each failure fits on one.
+                    async move {
+                        sync.await.map_err(|e| {
+                            let e = format!("{e:#}");
+                            anyhow::anyhow!("{}", e.lines().next().unwrap_or_default())
+                        })
+                    }
+                })
+                .await?
+        };
+        let not_pulled = std::mem::take(&mut *not_pulled.lock().unwrap());
+
+        let mut synced = 0;
+        let mut failed = Vec::new();
+        for ((target, state), not_pulled) in targets.iter().zip(states).zip(not_pulled) {
+            match state {
+                TaskState::Done => match not_pulled {
+                    Some(reason) => skipped.push(format!(
+                        "{}: fetched, not fast-forwarded: {reason}",
+                        target.name
+                    )),
+                    None => synced += 1,
+                },
+                TaskState::Failed(e) => failed.push(format!("{}: {e}", target.name)),
+                TaskState::Cancelled => failed.push(format!("{}: cancelled", target.name)),
+                TaskState::Queued | TaskState::Running => {}
+            }
+        }
+
+        eprintln!("synced {synced} of {total} repositories");
+        for skip in &skipped {
+            eprintln!("  - skipped {skip}");
+        }
+        for e in &failed {
+            eprintln!("  ✗ {e}");
+        }
+        if !failed.is_empty() {
+            anyhow::bail!("failed to sync {} repositories", failed.len());
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a fetched checkout couldn't be fast-forwarded, from its status after
+/// the fetch. `None` if it was, or had nothing to fast-forward.
+fn not_fast_forwarded(status: &RepoStatus) -> Option<String> {
+    if status.branch.is_none() {
+        Some("detached HEAD".into())
+    } else if status.upstream.is_none() {
+        Some("no upstream branch".into())
+    } else if status.is_dirty() {
+        Some("uncommitted changes".into())
+    } else if status.has_diverged() {
+        Some(format!(
+            "diverged from {} ({} ahead, {} behind)",
+            status.upstream.as_deref().unwrap_or_default(),
+            status.ahead,
+            status.behind
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_why_a_checkout_was_not_fast_forwarded() {
+        let clean = RepoStatus {
+            branch: Some("main".into()),
+            upstream: Some("origin/main".into()),
+            ..Default::default()
+        };
+        let cases = [
+            ("up to date", clean.clone(), None),
+            ("ahead", RepoStatus { ahead: 1, ..clean.clone() }, None),
+            (
+                "detached",
+                RepoStatus { branch: None, ..clean.clone() },
+                Some("detached HEAD"),
+            ),
+            (
+                "no upstream",
+                RepoStatus { upstream: None, ..clean.clone() },
+                Some("no upstream branch"),
+            ),
+            (
+                "dirty",
+                RepoStatus { changes: 2, behind: 1, ..clean.clone() },
+                Some("uncommitted changes"),
+            ),
+            (
+                "diverged",
+                RepoStatus { ahead: 2, behind: 3, ..clean.clone() },
+                Some("diverged from origin/main (2 ahead, 3 behind)"),
+            ),
+        ];
+
+        for (name, status, expected) in cases {
+            assert_eq!(not_fast_forwarded(&status).as_deref(), expected, "{name}");
+        }
+    }
+}
@@ -1,14 +1,280 @@
-use crate::{app::App, cache::CacheApp, projects_list::ProjectsListApp};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::{
+    app::App, cache::CacheApp, git_provider::Repository, layout::PathLayoutApp,
+    projects_list::ProjectsListApp,
+};
 
 #[derive(clap::Parser)]
-pub struct Update {}
+pub struct Update {
+    /// Output the repository changes as JSON
+    #[arg(long = "json", default_value = "false")]
+    json: bool,
+}
 
 impl Update {
     pub async fn execute(&mut self, app: &'static App) -> anyhow::Result<()> {
+        let previous = app.cache().get_stale().await?;
+
         let repositories = app.projects_list().get_projects().await?;
 
         app.cache().update(&repositories).await?;
 
+        let changes = match &previous {
+            Some(previous) => RepositoryChanges::between(previous, &repositories),
+            None => RepositoryChanges::between(&[], &repositories),
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&changes.to_json(app))?);
+            return Ok(());
+        }
+
+        if previous.is_none() {
+            println!("cached {} repositories", repositories.len());
+            return Ok(());
+        }
+
+        changes.print(app);
+
         Ok(())
     }
 }
+
+/// Differences between two repository listings.
+#[derive(Debug, Default, PartialEq)]
+pub struct RepositoryChanges {
+    pub added: Vec<Repository>,
+    pub removed: Vec<Repository>,
+    /// Repositories which moved to a new path, as `(previous, current)`.
+    pub renamed: Vec<(Repository, Repository)>,
+}
+
+impl RepositoryChanges {
+    /// Compares two listings by path. Repositories that disappeared from one
+    /// path and appeared at another are reported as renamed when the provider
+    /// id matches, or, if either side lacks an id, when the provider and
+    /// repository name match (an ownership transfer).
+    pub fn between(previous: &[Repository], current: &[Repository]) -> Self {
+        let previous_paths: BTreeSet<PathBuf> = previous.iter().map(|r| r.to_rel_path()).collect();
+        let current_paths: BTreeSet<PathBuf> = current.iter().map(|r| r.to_rel_path()).collect();
+
+        let mut removed: BTreeMap<PathBuf, &Repository> = previous
+            .iter()
+            .filter(|r| !current_paths.contains(&r.to_rel_path()))
+            .map(|r| (r.to_rel_path(), r))
+            .collect();
+        let mut added: BTreeMap<PathBuf, &Repository> = current
+            .iter()
+            .filter(|r| !previous_paths.contains(&r.to_rel_path()))
+            .map(|r| (r.to_rel_path(), r))
+            .collect();
+
+        let mut renamed = Vec::new();
+        let matchers: [fn(&Repository, &Repository) -> bool; 2] = [
+            |old, new| old.id != 0 && old.provider == new.provider && old.id == new.id,
+            |old, new| {
+                (old.id == 0 || new.id == 0)
+                    && old.provider == new.provider
+                    && old.repo_name == new.repo_name
+            },
+        ];
+        for matches in matchers {
+            let candidates: Vec<PathBuf> = removed.keys().cloned().collect();
+            for old_path in candidates {
+                let old = removed[&old_path];
+                let Some(new_path) = added
+                    .iter()
+                    .find(|(_, new)| matches(old, new))
+                    .map(|(path, _)| path.clone())
+                else {
+                    continue;
+                };
+
+                let new = added.remove(&new_path).expect("matched key to exist");
+                removed.remove(&old_path);
+                renamed.push((old.clone(), new.clone()));
+            }
+        }
+
+        renamed.sort_by_key(|(old, _)| old.to_rel_path());
+
+        Self {
+            added: added.into_values().cloned().collect(),
+            removed: removed.into_values().cloned().collect(),
+            renamed,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+
+    fn print(&self, app: &'static App) {
+        if self.is_empty() {
+            println!("no repository changes");
+            return;
+        }
+
+        if !self.added.is_empty() {
+            println!("added ({}):", self.added.len());
+            for repo in &self.added {
+                println!("  + {}", repo.to_rel_path().display());
+            }
+        }
+
+        if !self.removed.is_empty() {
+            println!("removed ({}):", self.removed.len());
+            for repo in &self.removed {
+                println!("  - {}", repo.to_rel_path().display());
+                if let Some(path) = local_clone(app, repo) {
+                    eprintln!(
+                        "warning: {} no longer exists upstream but is still cloned at {}",
+                        repo.to_rel_path().display(),
+                        path.display()
+                    );
+                }
+            }
+        }
+
+        if !self.renamed.is_empty() {
+            println!("renamed ({}):", self.renamed.len());
+            for (old, new) in &self.renamed {
+                println!(
+                    "  ~ {} -> {}",
+                    old.to_rel_path().display(),
+                    new.to_rel_path().display()
+                );
+            }
+        }
+    }
+
+    fn to_json(&self, app: &'static App) -> serde_json::Value {
+        serde_json::json!({
+            "added": self.added.iter().map(repository_json).collect::<Vec<_>>(),
+            "removed": self
+                .removed
+                .iter()
+                .map(|repo| {
+                    let mut value = repository_json(repo);
+                    value["local_clone"] = match local_clone(app, repo) {
+                        Some(path) => serde_json::Value::String(path.display().to_string()),
+                        None => serde_json::Value::Null,
+                    };
+                    value
+                })
+                .collect::<Vec<_>>(),
+            "renamed": self
+                .renamed
+                .iter()
+                .map(|(old, new)| {
+                    serde_json::json!({
+                        "from": repository_json(old),
+                        "to": repository_json(new),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn repository_json(repo: &Repository) -> serde_json::Value {
+    serde_json::json!({
+        "id": repo.id,
+        "provider": repo.provider,
+        "owner": repo.owner,
+        "repo_name": repo.repo_name,
+        "ssh_url": repo.ssh_url,
+    })
+}
+
+/// Returns the path of the local clone of `repo`, if one exists.
+fn local_clone(app: &'static App, repo: &Repository) -> Option<PathBuf> {
+    let path = app.layout().path(repo);
+    path.exists().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(path: &str, id: u64) -> Repository {
+        let mut parts = path.split('/');
+        let (provider, owner, repo_name) = (
+            parts.next().unwrap(),
+            parts.next().unwrap(),
+            parts.next().unwrap(),
+        );
+
+        Repository {
+            provider: provider.into(),
+            owner: owner.into(),
+            repo_name: repo_name.into(),
+            ssh_url: format!("ssh://git@{provider}/{owner}/{repo_name}.git"),
+            id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_added_and_removed_repositories() {
+        let previous = vec![repo("github.com/org/kept", 1), repo("github.com/org/gone", 2)];
+        let current = vec![repo("github.com/org/kept", 1), repo("github.com/org/new", 3)];
+
+        let changes = RepositoryChanges::between(&previous, &current);
+
+        assert_eq!(
+            changes,
+            RepositoryChanges {
+                added: vec![repo("github.com/org/new", 3)],
+                removed: vec![repo("github.com/org/gone", 2)],
+                renamed: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn detects_renames_by_provider_id() {
+        let previous = vec![repo("github.com/org/old-name", 7)];
+        let current = vec![repo("github.com/other-org/new-name", 7)];
+
+        let changes = RepositoryChanges::between(&previous, &current);
+
+        assert_eq!(
+            changes,
+            RepositoryChanges {
+                added: vec![],
+                removed: vec![],
+                renamed: vec![(
+                    repo("github.com/org/old-name", 7),
+                    repo("github.com/other-org/new-name", 7)
+                )],
+            }
+        );
+    }
+
+    #[test]
+    fn detects_transfers_by_name_when_ids_are_unknown() {
+        let previous = vec![repo("github.com/org/service", 0)];
+        let current = vec![repo("github.com/other-org/service", 12)];
+
+        let changes = RepositoryChanges::between(&previous, &current);
+
+        assert_eq!(changes.renamed.len(), 1);
+        assert!(changes.added.is_empty());
+        assert!(changes.removed.is_empty());
+    }
+
+    #[test]
+    fn does_not_pair_unrelated_repositories_with_known_ids() {
+        let previous = vec![repo("github.com/org/service", 1)];
+        let current = vec![repo("github.com/other-org/service", 2)];
+
+        let changes = RepositoryChanges::between(&previous, &current);
+
+        assert!(changes.renamed.is_empty());
+        assert_eq!(changes.added.len(), 1);
+        assert_eq!(changes.removed.len(), 1);
+    }
+}
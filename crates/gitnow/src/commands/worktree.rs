@@ -5,13 +5,16 @@ use crate::{
     cache::load_repositories,
     chooser::Chooser,
     components::inline_command::InlineCommand,
-    fuzzy_matcher::FuzzyMatcherApp,
+    custom_command::CustomCommandApp,
+    git_provider::Repository,
+    history::{HistoryApp, HistoryKind},
     interactive::{InteractiveApp, StringItem},
     shell::ShellApp,
+    theme::ThemeApp,
     worktree::{sanitize_branch_name, WorktreeApp},
 };
 
-use super::root::RepositoryMatcher;
+use super::root::{select_repository, MatchArgs};
 
 #[derive(clap::Parser)]
 pub struct WorktreeCommand {
@@ -30,6 +33,9 @@ pub struct WorktreeCommand {
     /// Skip spawning a shell in the worktree
     #[arg(long = "no-shell", default_value = "false")]
     no_shell: bool,
+
+    #[command(flatten)]
+    matching: MatchArgs,
 }
 
 impl WorktreeCommand {
@@ -40,14 +46,13 @@ impl WorktreeCommand {
         // Step 2: Select repository
         let repo = match &self.search {
             Some(needle) => {
-                let matched_repos = app
-                    .fuzzy_matcher()
-                    .match_repositories(needle, &repositories);
-
-                matched_repos
-                    .first()
-                    .ok_or(anyhow::anyhow!("failed to find repository"))?
-                    .to_owned()
+                let Some(repo) =
+                    select_repository(app, needle, &repositories, &self.matching)?
+                else {
+                    return Ok(());
+                };
+
+                repo
             }
             None => app
                 .interactive()
@@ -57,98 +62,128 @@ impl WorktreeCommand {
 
         tracing::debug!("selected repo: {}", repo.to_rel_path().display());
 
-        let wt = app.worktree();
-        let (_project_path, bare_path) = wt.paths(&repo);
-
-        // Step 3: Ensure bare clone exists
-        if !bare_path.exists() {
-            if std::io::stdout().is_terminal() && !self.no_shell {
-                let mut wrap_cmd =
-                    InlineCommand::new(format!("cloning: {}", repo.to_rel_path().display()));
-                let wt = app.worktree();
-                let repo_clone = repo.clone();
-                let bare_path_clone = bare_path.clone();
-                wrap_cmd
-                    .execute(move || async move {
-                        wt.ensure_bare_clone(&repo_clone, &bare_path_clone).await?;
-                        Ok(())
-                    })
-                    .await?;
-            } else {
-                eprintln!("bare-cloning repository...");
-                wt.ensure_bare_clone(&repo, &bare_path).await?;
-            }
-        }
-
-        // Step 4: List branches
-        let branches = app.worktree().list_branches(&bare_path).await?;
+        open_worktree(app, chooser, &repo, self.branch.as_deref(), self.no_shell).await
+    }
+}
 
-        if branches.is_empty() {
-            anyhow::bail!("no branches found for {}", repo.to_rel_path().display());
+/// Creates (if needed) and enters a worktree of `repo`, bare-cloning it
+/// first. Prompts for the branch unless one is given.
+pub async fn open_worktree(
+    app: &'static App,
+    chooser: &Chooser,
+    repo: &Repository,
+    branch: Option<&str>,
+    no_shell: bool,
+) -> anyhow::Result<()> {
+    let wt = app.worktree();
+    let (_project_path, bare_path) = wt.paths(repo);
+
+    // Step 3: Ensure bare clone exists
+    if !bare_path.exists() {
+        if std::io::stdout().is_terminal() && !no_shell {
+            let mut wrap_cmd =
+                InlineCommand::new(format!("cloning: {}", repo.to_rel_path().display()))
+                    .with_theme(app.theme());
+            let wt = app.worktree();
+            let repo_clone = repo.clone();
+            let bare_path_clone = bare_path.clone();
+            wrap_cmd
+                .execute_with_progress(move |progress| async move {
+                    wt.ensure_bare_clone(&repo_clone, &bare_path_clone, Some(&progress))
+                        .await?;
+                    Ok(())
+                })
+                .await?;
+        } else {
+            eprintln!("bare-cloning repository...");
+            wt.ensure_bare_clone(repo, &bare_path, None).await?;
         }
+    }
 
-        // Step 5: Select branch
-        let branch = match &self.branch {
-            Some(b) => {
-                if !branches.contains(b) {
-                    anyhow::bail!(
-                        "branch '{}' not found. Available branches: {}",
-                        b,
-                        branches.join(", ")
-                    );
-                }
-                b.clone()
-            }
-            None => {
-                let items: Vec<StringItem> =
-                    branches.into_iter().map(StringItem).collect();
-
-                let selected = app
-                    .interactive()
-                    .interactive_search_items(&items)?
-                    .ok_or(anyhow::anyhow!("no branch selected"))?;
+    // Step 4: List branches
+    let branches = app.worktree().list_branches(repo, &bare_path).await?;
 
-                selected.0
-            }
-        };
+    if branches.is_empty() {
+        anyhow::bail!("no branches found for {}", repo.to_rel_path().display());
+    }
 
-        // Step 6: Create worktree at <project_path>/<sanitized_branch>/
-        let sanitized = sanitize_branch_name(&branch);
-        let (project_path, _) = app.worktree().paths(&repo);
-        let worktree_path = project_path.join(&sanitized);
-
-        if !worktree_path.exists() {
-            if std::io::stdout().is_terminal() && !self.no_shell {
-                let mut wrap_cmd =
-                    InlineCommand::new(format!("creating worktree: {}", &branch));
-                let wt = app.worktree();
-                let bare_path = bare_path.clone();
-                let worktree_path = worktree_path.clone();
-                let branch = branch.clone();
-                wrap_cmd
-                    .execute(move || async move {
-                        wt.add_worktree(&bare_path, &worktree_path, &branch)
-                            .await?;
-                        Ok(())
-                    })
-                    .await?;
-            } else {
-                eprintln!("creating worktree for branch '{}'...", &branch);
-                app.worktree()
-                    .add_worktree(&bare_path, &worktree_path, &branch)
-                    .await?;
+    // Step 5: Select branch
+    let branch = match branch {
+        Some(b) => {
+            if !branches.iter().any(|branch| branch == b) {
+                anyhow::bail!(
+                    "branch '{}' not found. Available branches: {}",
+                    b,
+                    branches.join(", ")
+                );
             }
-        } else {
-            tracing::info!("worktree already exists at {}", worktree_path.display());
+            b.to_string()
         }
+        None => {
+            let items: Vec<StringItem> =
+                branches.into_iter().map(StringItem).collect();
 
-        // Step 7: Enter shell or print path
-        if !self.no_shell && !chooser.is_active() {
-            app.shell().spawn_shell_at(&worktree_path).await?;
+            let selected = app
+                .interactive()
+                .interactive_search_items(&items)?
+                .ok_or(anyhow::anyhow!("no branch selected"))?;
+
+            selected.0
+        }
+    };
+
+    // Step 6: Create worktree at <project_path>/<sanitized_branch>/
+    let sanitized = sanitize_branch_name(&branch);
+    let (project_path, _) = app.worktree().paths(repo);
+    let worktree_path = project_path.join(&sanitized);
+
+    if !worktree_path.exists() {
+        if std::io::stdout().is_terminal() && !no_shell {
+            let mut wrap_cmd =
+                InlineCommand::new(format!("creating worktree: {}", &branch))
+                    .with_theme(app.theme());
+            let wt = app.worktree();
+            let bare_path = bare_path.clone();
+            let worktree_path = worktree_path.clone();
+            let branch = branch.clone();
+            let repo = repo.clone();
+            wrap_cmd
+                .execute(move || async move {
+                    wt.add_worktree(&repo, &bare_path, &worktree_path, &branch)
+                        .await?;
+                    Ok(())
+                })
+                .await?;
         } else {
-            chooser.set(&worktree_path)?;
+            eprintln!("creating worktree for branch '{}'...", &branch);
+            app.worktree()
+                .add_worktree(repo, &bare_path, &worktree_path, &branch)
+                .await?;
         }
 
-        Ok(())
+        app.custom_command(repo)
+            .execute_post_worktree_add_command(&worktree_path, &bare_path, &branch)
+            .await?;
+    } else {
+        tracing::info!("worktree already exists at {}", worktree_path.display());
     }
+
+    app.history().try_record(
+        HistoryKind::Worktree,
+        repo.to_rel_path().display().to_string(),
+        &worktree_path,
+    );
+
+    app.custom_command(repo)
+        .execute_on_enter_command(&worktree_path, HistoryKind::Worktree)
+        .await?;
+
+    // Step 7: Enter shell or print path
+    if !no_shell && !chooser.is_active() {
+        app.shell().spawn_shell_at(&worktree_path).await?;
+    } else {
+        chooser.set(&worktree_path)?;
+    }
+
+    Ok(())
 }
@@ -1,7 +1,11 @@
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
+use progress_bar::Progress;
+
 pub mod inline_command;
+pub mod progress_bar;
 pub mod spinner;
+pub mod task_list;
 
 #[derive(Debug, PartialEq)]
 pub enum Msg {
@@ -9,6 +13,7 @@ pub enum Msg {
     Tick,
     Success,
     Failure(String),
+    Progress(Progress),
 }
 
 pub struct Command {
@@ -8,16 +8,21 @@ use ratatui::{
     widgets::{Block, Padding},
 };
 
-use crate::components::BatchCommand;
+use crate::{components::BatchCommand, theme::Theme};
 
 use super::{
     Dispatch, IntoCommand, Msg, Receiver, create_dispatch,
+    progress_bar::{Progress, ProgressBar, ProgressSender},
     spinner::{Spinner, SpinnerState},
 };
 
 pub struct InlineCommand {
     spinner: SpinnerState,
     heading: String,
+    theme: Theme,
+    /// Set once the command has reported success or failure.
+    finished: bool,
+    progress: Option<Progress>,
 }
 
 impl InlineCommand {
@@ -25,32 +30,65 @@ impl InlineCommand {
         Self {
             spinner: SpinnerState::default(),
             heading: heading.into(),
+            theme: Theme::default(),
+            finished: false,
+            progress: None,
         }
     }
 
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Runs `func` behind a spinner and returns its result. Quitting with
+    /// Esc cancels it and returns an error.
     pub async fn execute<F, Fut>(&mut self, func: F) -> anyhow::Result<()>
     where
         F: FnOnce() -> Fut + Send + Sync + 'static,
         Fut: futures::Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.execute_with_progress(|_| func()).await
+    }
+
+    /// Like [`InlineCommand::execute`], but `func` can report progress,
+    /// which is drawn below the spinner.
+    pub async fn execute_with_progress<F, Fut>(&mut self, func: F) -> anyhow::Result<()>
+    where
+        F: FnOnce(ProgressSender) -> Fut + Send + Sync + 'static,
+        Fut: futures::Future<Output = anyhow::Result<()>> + Send + 'static,
     {
         tracing::trace!("starting inline terminal");
 
         let mut terminal = ratatui::init_with_options(TerminalOptions {
-            viewport: Viewport::Inline(3),
+            viewport: Viewport::Inline(self.height()),
         });
 
         let (dispatch, mut receiver) = create_dispatch();
         let mut event_stream = crossterm::event::EventStream::new();
         let guard = TerminalGuard;
 
+        let (progress, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
         tokio::spawn({
             let dispatch = dispatch.clone();
 
             async move {
-                match func().await {
+                while let Some(progress) = progress_rx.recv().await {
+                    dispatch.send(Msg::Progress(progress));
+                }
+            }
+        });
+
+        let handle = tokio::spawn({
+            let dispatch = dispatch.clone();
+
+            async move {
+                let result = func(progress).await;
+                match &result {
                     Ok(_) => dispatch.send(Msg::Success),
                     Err(e) => dispatch.send(Msg::Failure(e.to_string())),
                 }
+                result
             }
         });
 
@@ -72,7 +110,12 @@ impl InlineCommand {
 
         println!();
 
-        Ok(())
+        if !self.finished {
+            handle.abort();
+            anyhow::bail!("{} cancelled", self.heading);
+        }
+
+        handle.await?
     }
 
     async fn update(
@@ -136,17 +179,39 @@ impl InlineCommand {
         Ok(false)
     }
 
-    fn draw(&mut self, frame: &mut Frame<'_>) {
-        let spinner = Spinner::new(Span::from(&self.heading));
+    /// A border or title takes the place of the top padding.
+    fn block(&self) -> Block<'static> {
+        match self.theme.block() {
+            Some(block) => block.padding(Padding::horizontal(1)),
+            None => Block::new().padding(Padding::new(2, 2, 1, 0)),
+        }
+    }
 
-        let block = Block::new().padding(Padding::symmetric(2, 1));
+    /// Rows for the spinner and progress lines plus the block around them.
+    fn height(&self) -> u16 {
+        let area = Rect::new(0, 0, 10, 10);
+        2 + area.height - self.block().inner(area).height
+    }
 
+    fn draw(&mut self, frame: &mut Frame<'_>) {
+        let block = self.block();
+        let [_, progress_area] =
+            Layout::vertical([Constraint::Length(1); 2]).areas(block.inner(frame.area()));
+
+        let spinner = Spinner::new(Span::from(&self.heading)).style(self.theme.spinner);
         StatefulWidget::render(
             spinner.block(block),
             frame.area(),
             frame.buffer_mut(),
             &mut self.spinner,
         );
+
+        if let Some(progress) = &self.progress {
+            let bar = ProgressBar::new(progress)
+                .style(self.theme.spinner)
+                .track_style(self.theme.border);
+            frame.render_widget(bar, progress_area);
+        }
     }
 
     fn handle_key_event(&mut self, event: crossterm::event::Event) -> Option<Msg> {
@@ -166,12 +231,17 @@ impl InlineCommand {
 
         let mut batch = BatchCommand::default();
 
-        match msg {
+        match &msg {
             Msg::Quit => {}
             Msg::Tick => {}
-            Msg::Success => return Msg::Quit.into_command(),
+            Msg::Progress(progress) => self.progress = Some(progress.clone()),
+            Msg::Success => {
+                self.finished = true;
+                return Msg::Quit.into_command();
+            }
             Msg::Failure(f) => {
-                tracing::error!("command failed: {}", f);
+                tracing::debug!("command failed: {}", f);
+                self.finished = true;
                 return Msg::Quit.into_command();
             }
         }
@@ -182,8 +252,9 @@ impl InlineCommand {
     }
 }
 
+/// Restores the terminal after an inline viewport, even on early return.
 #[derive(Default)]
-struct TerminalGuard;
+pub(super) struct TerminalGuard;
 
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
@@ -0,0 +1,69 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+/// Width of the bar itself, excluding the percentage and label.
+const BAR_WIDTH: usize = 24;
+
+/// Progress reported by a long-running command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Progress {
+    /// A known phase with its completion, e.g. `Receiving objects` at 45%.
+    Percent { label: String, percent: u8 },
+    /// The latest line of output from a command we can't parse.
+    Message(String),
+}
+
+pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<Progress>;
+
+/// Renders [`Progress`] on one line: a bar with the percentage and phase, or
+/// the latest message.
+pub struct ProgressBar<'a> {
+    progress: &'a Progress,
+    style: Style,
+    track_style: Style,
+}
+
+impl<'a> ProgressBar<'a> {
+    pub fn new(progress: &'a Progress) -> Self {
+        Self {
+            progress,
+            style: Style::default(),
+            track_style: Style::default(),
+        }
+    }
+
+    /// Style of the filled part of the bar.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Style of the empty part of the bar and of plain messages.
+    pub fn track_style(mut self, style: Style) -> Self {
+        self.track_style = style;
+        self
+    }
+}
+
+impl Widget for ProgressBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let line = match self.progress {
+            Progress::Percent { label, percent } => {
+                let filled = BAR_WIDTH * (*percent).min(100) as usize / 100;
+                Line::from(vec![
+                    Span::styled("█".repeat(filled), self.style),
+                    Span::styled("░".repeat(BAR_WIDTH - filled), self.track_style),
+                    Span::from(format!(" {percent:>3}% {label}")),
+                ])
+            }
+            Progress::Message(message) => Line::styled(message.clone(), self.track_style),
+        };
+
+        Paragraph::new(line).render(area, buf)
+    }
+}
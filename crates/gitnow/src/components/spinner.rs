@@ -1,6 +1,7 @@
 use std::time::{Duration, Instant};
 
 use ratatui::{
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Paragraph, StatefulWidget, Widget},
 };
@@ -10,11 +11,22 @@ use super::{BatchCommand, IntoCommand, Msg};
 pub struct Spinner<'a> {
     span: Span<'a>,
     block: Option<Block<'a>>,
+    style: Style,
 }
 
 impl<'a> Spinner<'a> {
     pub fn new(span: Span<'a>) -> Self {
-        Self { span, block: None }
+        Self {
+            span,
+            block: None,
+            style: Style::default(),
+        }
+    }
+
+    /// Style of the spinner glyph.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
     }
 
     pub fn block(mut self, block: Block<'a>) -> Self {
@@ -36,7 +48,11 @@ impl StatefulWidget for Spinner<'_> {
             .get((state.frame) % MINIDOT_FRAMES.len())
             .expect("to find a valid static frame");
 
-        let line = Line::from(vec![Span::from(*frame), Span::from(" "), self.span]);
+        let line = Line::from(vec![
+            Span::styled(*frame, self.style),
+            Span::from(" "),
+            self.span,
+        ]);
 
         let para = Paragraph::new(vec![line]);
         let para = if let Some(block) = self.block {
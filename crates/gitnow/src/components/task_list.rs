@@ -0,0 +1,199 @@
+use std::{future::Future, io::IsTerminal, sync::Arc, time::Duration};
+
+use crossterm::event::{Event, EventStream, KeyCode, KeyModifiers};
+use futures::StreamExt;
+use ratatui::{TerminalOptions, Viewport, prelude::*, widgets::Paragraph};
+use tokio::sync::{Semaphore, mpsc};
+
+use crate::theme::Theme;
+
+use super::{
+    Msg,
+    inline_command::TerminalGuard,
+    spinner::{Spinner, SpinnerState},
+};
+
+/// Most tasks shown at once; the footer counts the rest.
+const MAX_VISIBLE_TASKS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskState {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl TaskState {
+    /// Running tasks are listed first, then failures, queued and done.
+    fn order(&self) -> u8 {
+        match self {
+            TaskState::Running => 0,
+            TaskState::Failed(_) | TaskState::Cancelled => 1,
+            TaskState::Queued => 2,
+            TaskState::Done => 3,
+        }
+    }
+}
+
+/// Runs labelled tasks concurrently, drawing a live list below the prompt
+/// with a spinner per running task. Without a terminal, each finished task
+/// is printed as a line instead.
+pub struct TaskList {
+    labels: Vec<String>,
+    states: Vec<TaskState>,
+    spinner: SpinnerState,
+    theme: Theme,
+}
+
+impl TaskList {
+    pub fn new(labels: Vec<String>) -> Self {
+        Self {
+            states: vec![TaskState::Queued; labels.len()],
+            labels,
+            spinner: SpinnerState::default(),
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Runs `task(i)` for every label, at most `concurrency` at a time, and
+    /// returns each task's final state. Esc or Ctrl-C cancels unfinished
+    /// tasks.
+    pub async fn execute<F, Fut>(
+        mut self,
+        concurrency: usize,
+        task: F,
+    ) -> anyhow::Result<Vec<TaskState>>
+    where
+        F: Fn(usize) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let (tx, mut updates) = mpsc::unbounded_channel();
+        let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+        let handles: Vec<_> = (0..self.labels.len())
+            .map(|i| {
+                let future = task(i);
+                let tx = tx.clone();
+                let semaphore = Arc::clone(&semaphore);
+
+                tokio::spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    let _ = tx.send((i, TaskState::Running));
+                    let state = match future.await {
+                        Ok(()) => TaskState::Done,
+                        Err(e) => TaskState::Failed(format!("{e:#}")),
+                    };
+                    let _ = tx.send((i, state));
+                })
+            })
+            .collect();
+        drop(tx);
+
+        if !std::io::stdout().is_terminal() {
+            while let Some((i, state)) = updates.recv().await {
+                match &state {
+                    TaskState::Done => eprintln!("done: {}", self.labels[i]),
+                    TaskState::Failed(e) => eprintln!("failed: {}: {e}", self.labels[i]),
+                    _ => {}
+                }
+                self.states[i] = state;
+            }
+
+            return Ok(self.states);
+        }
+
+        let mut terminal = ratatui::init_with_options(TerminalOptions {
+            viewport: Viewport::Inline(self.labels.len().min(MAX_VISIBLE_TASKS) as u16 + 1),
+        });
+        let guard = TerminalGuard;
+        let mut events = EventStream::new();
+        let mut ticks = tokio::time::interval(Duration::from_millis(1000 / 20));
+
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            tokio::select! {
+                _ = ticks.tick() => {
+                    self.spinner.update(&Msg::Tick);
+                }
+                update = updates.recv() => match update {
+                    Some((i, state)) => self.states[i] = state,
+                    None => break,
+                },
+                Some(Ok(Event::Key(key))) = events.next() => {
+                    let cancel = key.code == KeyCode::Esc
+                        || (key.code == KeyCode::Char('c')
+                            && key.modifiers.contains(KeyModifiers::CONTROL));
+                    if cancel {
+                        handles.iter().for_each(|handle| handle.abort());
+                        for state in &mut self.states {
+                            if matches!(state, TaskState::Queued | TaskState::Running) {
+                                *state = TaskState::Cancelled;
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        terminal.draw(|frame| frame.buffer_mut().reset())?;
+        drop(guard);
+
+        Ok(self.states)
+    }
+
+    fn draw(&mut self, frame: &mut Frame<'_>) {
+        let mut order: Vec<usize> = (0..self.labels.len()).collect();
+        order.sort_by_key(|&i| self.states[i].order());
+        let visible = order.len().min(MAX_VISIBLE_TASKS);
+
+        let rows = Layout::vertical(vec![Constraint::Length(1); visible + 1]).split(frame.area());
+        for (&i, &row) in order.iter().zip(rows.iter()) {
+            let label = self.labels[i].as_str();
+            let line = match &self.states[i] {
+                TaskState::Running => {
+                    let spinner = Spinner::new(Span::from(label)).style(self.theme.spinner);
+                    StatefulWidget::render(spinner, row, frame.buffer_mut(), &mut self.spinner);
+                    continue;
+                }
+                TaskState::Queued => Line::from(format!("· {label}")).style(self.theme.border),
+                TaskState::Done => Line::from(vec![Span::from("✓ ").green(), Span::from(label)]),
+                TaskState::Failed(e) => Line::from(vec![
+                    Span::from("✗ ").red(),
+                    Span::from(label),
+                    Span::from(format!(": {e}")).red(),
+                ]),
+                TaskState::Cancelled => Line::from(vec![
+                    Span::from("✗ ").red(),
+                    Span::from(format!("{label} (cancelled)")).style(self.theme.border),
+                ]),
+            };
+            frame.render_widget(Paragraph::new(line), row);
+        }
+
+        let count = |f: fn(&TaskState) -> bool| self.states.iter().filter(|s| f(s)).count();
+        let done = count(|s| *s == TaskState::Done);
+        let failed = count(|s| matches!(s, TaskState::Failed(_) | TaskState::Cancelled));
+        let hidden = self.labels.len() - visible;
+
+        let mut footer = format!("{done}/{} done", self.labels.len());
+        if failed > 0 {
+            footer.push_str(&format!(", {failed} failed"));
+        }
+        if hidden > 0 {
+            footer.push_str(&format!(", {hidden} more not shown"));
+        }
+        footer.push_str(" | Esc: cancel");
+        frame.render_widget(
+            Paragraph::new(Line::from(footer).style(self.theme.border)),
+            rows[visible],
+        );
+    }
+}
@@ -1,8 +1,15 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    git_provider::Repository,
+    keymap::{KeyBindingList, PickerAction},
+};
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Config {
     #[serde(default)]
@@ -20,13 +27,23 @@ pub struct Settings {
     #[serde(default)]
     pub cache: Cache,
 
+    pub pre_clone_command: Option<CommandList>,
     pub post_clone_command: Option<PostCloneCommand>,
     pub post_update_command: Option<PostUpdateCommand>,
 
+    /// Run before entering a repository, worktree or project, whether by
+    /// spawning a shell or through the shell integration.
+    pub on_enter_command: Option<CommandList>,
+
     /// Minijinja template for the clone command.
-    /// Default: "git clone {{ ssh_url }} {{ path }}"
+    /// Default: "git clone --progress {{ clone_args }} {{ ssh_url }} {{ path }}"
     pub clone_command: Option<String>,
 
+    /// Named sets of clone options for large repositories, used with
+    /// `--profile <name>` or for repositories matching their patterns.
+    #[serde(default)]
+    pub clone_profiles: Vec<CloneProfile>,
+
     /// Worktree configuration.
     #[serde(default)]
     pub worktree: Option<WorktreeSettings>,
@@ -34,6 +51,375 @@ pub struct Settings {
     /// Project scratch-pad configuration.
     #[serde(default)]
     pub project: Option<ProjectSettings>,
+
+    /// Search ranking configuration.
+    #[serde(default)]
+    pub search: Option<SearchSettings>,
+
+    /// Picker keybindings, mapping actions to one or more keys, e.g.
+    /// `delete_word = ["ctrl-w", "alt-backspace"]`. Unset actions keep their
+    /// emacs-style defaults.
+    #[serde(default)]
+    pub keybindings: Option<BTreeMap<PickerAction, KeyBindingList>>,
+
+    /// Look and layout of the pickers and progress spinners.
+    #[serde(default)]
+    pub ui: Option<UiSettings>,
+
+    /// Overrides for groups of repositories, see [`Settings::for_repository`].
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+
+    /// Local mirrors that clones borrow objects from.
+    #[serde(default)]
+    pub mirrors: Option<MirrorSettings>,
+
+    /// How existing clones are updated when opened.
+    #[serde(default)]
+    pub update: Option<UpdateSettings>,
+}
+
+impl Settings {
+    /// These settings with the first rule matching `repository` applied on
+    /// top. Anything the rule leaves unset keeps its global value.
+    pub fn for_repository(&self, repository: &Repository) -> Cow<'_, Settings> {
+        let rel_path = repository.to_rel_path().display().to_string();
+        let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| rule.repositories.iter().any(|p| p.matches(&rel_path)))
+        else {
+            return Cow::Borrowed(self);
+        };
+
+        let mut settings = self.clone();
+        if let Some(clone_command) = &rule.clone_command {
+            settings.clone_command = Some(clone_command.clone());
+        }
+        if let Some(pre_clone_command) = &rule.pre_clone_command {
+            settings.pre_clone_command = Some(pre_clone_command.clone());
+        }
+        if let Some(post_clone_command) = &rule.post_clone_command {
+            settings.post_clone_command = Some(post_clone_command.clone());
+        }
+        if let Some(post_update_command) = &rule.post_update_command {
+            settings.post_update_command = Some(post_update_command.clone());
+        }
+        if let Some(on_enter_command) = &rule.on_enter_command {
+            settings.on_enter_command = Some(on_enter_command.clone());
+        }
+        if let Some(worktree) = &rule.worktree {
+            let global = settings.worktree.take().unwrap_or_default();
+            settings.worktree = Some(WorktreeSettings {
+                clone_command: worktree.clone_command.clone().or(global.clone_command),
+                add_command: worktree.add_command.clone().or(global.add_command),
+                list_branches_command: worktree
+                    .list_branches_command
+                    .clone()
+                    .or(global.list_branches_command),
+                post_add_command: worktree
+                    .post_add_command
+                    .clone()
+                    .or(global.post_add_command),
+            });
+        }
+
+        Cow::Owned(settings)
+    }
+}
+
+/// Clone, worktree and hook settings for the repositories matching
+/// `repositories`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Rule {
+    pub repositories: Vec<RepositoryPattern>,
+
+    pub clone_command: Option<String>,
+    pub pre_clone_command: Option<CommandList>,
+    pub post_clone_command: Option<PostCloneCommand>,
+    pub post_update_command: Option<PostUpdateCommand>,
+    pub on_enter_command: Option<CommandList>,
+
+    #[serde(default)]
+    pub worktree: Option<WorktreeSettings>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct UiSettings {
+    #[serde(default)]
+    pub colors: Option<UiColors>,
+
+    /// Shown in front of the selected item.
+    /// Default: "> "
+    pub highlight_symbol: Option<String>,
+
+    /// Default: "bottom_to_top", with the prompt below the list
+    pub list_direction: Option<UiListDirection>,
+
+    /// Draw the picker inline below the cursor with this height, either a
+    /// number of lines or a percentage of the terminal such as "40%".
+    /// Default: unset, using the whole screen
+    pub height: Option<UiHeight>,
+
+    /// Draw a border around pickers and spinners.
+    /// Default: false
+    pub border: Option<bool>,
+
+    /// Title shown at the top of pickers and spinners.
+    pub title: Option<String>,
+}
+
+/// Colours by name (`blue`, `light_green`), index (`42`) or hex (`#5f87ff`).
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct UiColors {
+    /// Default: "blue"
+    pub prompt: Option<UiColor>,
+    /// The selected item. Default: "white"
+    pub highlight: Option<UiColor>,
+    /// Characters matching the search. Default: "yellow"
+    pub matched: Option<UiColor>,
+    /// Borders and dimmed text. Default: "dark_gray"
+    pub border: Option<UiColor>,
+    /// Default: the terminal's foreground colour
+    pub spinner: Option<UiColor>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(try_from = "String", into = "String")]
+pub struct UiColor(pub ratatui::style::Color);
+
+impl TryFrom<String> for UiColor {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .parse()
+            .map(Self)
+            .map_err(|_| anyhow::anyhow!("invalid colour: {value}"))
+    }
+}
+
+impl From<UiColor> for String {
+    fn from(value: UiColor) -> Self {
+        value.0.to_string()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum UiListDirection {
+    #[default]
+    BottomToTop,
+    TopToBottom,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(try_from = "UiHeightValue", into = "UiHeightValue")]
+pub enum UiHeight {
+    Lines(u16),
+    Percent(u16),
+}
+
+impl UiHeight {
+    /// Height in lines on a terminal with `rows` rows.
+    pub fn lines(&self, rows: u16) -> u16 {
+        let lines = match self {
+            UiHeight::Lines(lines) => *lines,
+            UiHeight::Percent(percent) => (rows as u32 * *percent as u32 / 100) as u16,
+        };
+
+        lines.clamp(1, rows.max(1))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum UiHeightValue {
+    Lines(u16),
+    Text(String),
+}
+
+impl TryFrom<UiHeightValue> for UiHeight {
+    type Error = anyhow::Error;
+
+    fn try_from(value: UiHeightValue) -> Result<Self, Self::Error> {
+        match value {
+            UiHeightValue::Lines(lines) => Ok(UiHeight::Lines(lines)),
+            UiHeightValue::Text(text) => match text.strip_suffix('%') {
+                Some(percent) => match percent.trim().parse() {
+                    Ok(percent @ 1..=100) => Ok(UiHeight::Percent(percent)),
+                    _ => anyhow::bail!("invalid height percentage: {text}"),
+                },
+                None => text
+                    .trim()
+                    .parse()
+                    .map(UiHeight::Lines)
+                    .map_err(|_| anyhow::anyhow!("invalid height: {text}")),
+            },
+        }
+    }
+}
+
+impl From<UiHeight> for UiHeightValue {
+    fn from(value: UiHeight) -> Self {
+        match value {
+            UiHeight::Lines(lines) => UiHeightValue::Lines(lines),
+            UiHeight::Percent(percent) => UiHeightValue::Text(format!("{percent}%")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct MirrorSettings {
+    /// Keep a mirror of every cloned repository and clone with
+    /// `--reference-if-able <mirror> --dissociate`.
+    /// Default: false
+    pub enabled: Option<bool>,
+
+    /// Where mirrors are stored.
+    /// Default: "<cache location>/mirrors"
+    pub directory: Option<String>,
+
+    /// Template for creating a mirror.
+    /// Default: "git clone --mirror --progress {{ ssh_url }} {{ mirror_path }}"
+    pub clone_command: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct CloneProfile {
+    pub name: String,
+
+    /// Repositories that use this profile unless `--profile` is given, as
+    /// `provider/owner/repo` globs such as "github.com/bigcorp/*". The first
+    /// matching profile wins.
+    #[serde(default)]
+    pub repositories: Vec<RepositoryPattern>,
+
+    /// Only fetch this many commits of history (`--depth`).
+    pub depth: Option<u32>,
+
+    /// Partial clone filter (`--filter`), e.g. "blob:none".
+    pub filter: Option<String>,
+
+    /// Only check out these directories (`git sparse-checkout set`).
+    #[serde(default)]
+    pub sparse_paths: Vec<String>,
+}
+
+/// A glob matched against `provider/owner/repo`, where `*` matches within
+/// one path segment and `**` across segments, or a regex prefixed with
+/// `regex:`. Both ignore case.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct RepositoryPattern {
+    pattern: String,
+    regex: regex::Regex,
+}
+
+impl RepositoryPattern {
+    pub fn matches(&self, rel_path: &str) -> bool {
+        self.regex.is_match(rel_path)
+    }
+}
+
+impl PartialEq for RepositoryPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl TryFrom<String> for RepositoryPattern {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let regex = match value.strip_prefix("regex:") {
+            Some(regex) => format!("(?i){regex}"),
+            None => {
+                let mut regex = String::from("(?i)^");
+                let mut chars = value.chars().peekable();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '*' if chars.next_if_eq(&'*').is_some() => regex.push_str(".*"),
+                        '*' => regex.push_str("[^/]*"),
+                        '?' => regex.push_str("[^/]"),
+                        c => regex.push_str(&regex::escape(&c.to_string())),
+                    }
+                }
+                regex.push('$');
+                regex
+            }
+        };
+
+        let regex = regex::Regex::new(&regex)
+            .with_context(|| format!("invalid repository pattern: {value}"))?;
+
+        Ok(Self {
+            pattern: value,
+            regex,
+        })
+    }
+}
+
+impl From<RepositoryPattern> for String {
+    fn from(value: RepositoryPattern) -> Self {
+        value.pattern
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SearchSettings {
+    /// How strongly recently and frequently opened repositories are boosted
+    /// in search results. Set to 0 to rank purely by match quality.
+    /// Default: 1.0
+    pub frecency_weight: Option<f64>,
+
+    /// What to do when a non-interactive search is ambiguous.
+    /// Default: "first"
+    pub on_ambiguous: Option<AmbiguityAction>,
+
+    /// A search is ambiguous when the runner-up scores within this many
+    /// points of the best match, and neither is an exact match.
+    /// Default: 8
+    pub ambiguity_threshold: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone, Copy, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AmbiguityAction {
+    /// Use the best match.
+    #[default]
+    First,
+    /// Fail and list the candidates.
+    Fail,
+    /// Open the interactive picker with the candidates.
+    Interactive,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct UpdateSettings {
+    /// Default: "none"
+    #[serde(default)]
+    pub strategy: UpdateStrategy,
+
+    /// Only update when the last fetch is older than this many hours.
+    pub interval_hours: Option<u64>,
+
+    /// Seconds to wait for the update before opening the repository anyway.
+    /// Default: 10
+    pub timeout: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStrategy {
+    /// Leave existing clones alone.
+    #[default]
+    None,
+    /// `git fetch`.
+    Fetch,
+    /// `git fetch`, then fast-forward the checked out branch when it is
+    /// clean and hasn't diverged.
+    Pull,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -50,12 +436,22 @@ pub struct ProjectSettings {
     /// Automatically delete projects older than this many days whenever a
     /// project command runs.
     pub auto_delete_older_than_days: Option<u32>,
+
+    /// Hooks around project changes. `pre_create_command` and
+    /// `post_delete_command` run in the projects directory, the others in
+    /// the project.
+    pub pre_create_command: Option<CommandList>,
+    pub post_create_command: Option<CommandList>,
+    pub post_add_command: Option<CommandList>,
+    pub post_remove_command: Option<CommandList>,
+    pub pre_delete_command: Option<CommandList>,
+    pub post_delete_command: Option<CommandList>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct WorktreeSettings {
     /// Template for bare-cloning a repository.
-    /// Default: "git clone --bare {{ ssh_url }} {{ bare_path }}"
+    /// Default: "git clone --bare --progress {{ clone_args }} {{ ssh_url }} {{ bare_path }}"
     pub clone_command: Option<String>,
 
     /// Template for adding a worktree.
@@ -65,18 +461,21 @@ pub struct WorktreeSettings {
     /// Template for listing branches.
     /// Default: "git -C {{ bare_path }} branch -r --format=%(refname:short)"
     pub list_branches_command: Option<String>,
+
+    /// Hooks run in a newly added worktree.
+    pub post_add_command: Option<CommandList>,
 }
 
-/// A list of shell commands that can be specified as a single string or an array in TOML.
+/// A list of hooks that can be specified as a single hook or an array in TOML.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(untagged)]
 pub enum CommandList {
-    Single(String),
-    Multiple(Vec<String>),
+    Single(Hook),
+    Multiple(Vec<Hook>),
 }
 
 impl CommandList {
-    pub fn get_commands(&self) -> Vec<String> {
+    pub fn get_commands(&self) -> Vec<Hook> {
         match self.clone() {
             CommandList::Single(item) => vec![item],
             CommandList::Multiple(items) => items,
@@ -84,6 +483,50 @@ impl CommandList {
     }
 }
 
+/// A shell command run at a hook point, either as a plain string or as a
+/// table with options.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Hook {
+    Command(String),
+    Detailed {
+        command: String,
+        /// Abort the gitnow command when the hook fails, instead of warning.
+        #[serde(default)]
+        fail_on_error: bool,
+        /// Seconds after which the hook is killed and counts as failed.
+        timeout: Option<u64>,
+    },
+}
+
+impl Hook {
+    pub fn command(&self) -> &str {
+        match self {
+            Hook::Command(command) | Hook::Detailed { command, .. } => command,
+        }
+    }
+
+    pub fn fail_on_error(&self) -> bool {
+        match self {
+            Hook::Command(_) => false,
+            Hook::Detailed { fail_on_error, .. } => *fail_on_error,
+        }
+    }
+
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        match self {
+            Hook::Command(_) => None,
+            Hook::Detailed { timeout, .. } => timeout.map(std::time::Duration::from_secs),
+        }
+    }
+}
+
+impl From<&str> for Hook {
+    fn from(command: &str) -> Self {
+        Hook::Command(command.into())
+    }
+}
+
 /// Backwards-compatible type aliases.
 pub type PostCloneCommand = CommandList;
 pub type PostUpdateCommand = CommandList;
@@ -91,6 +534,12 @@ pub type PostUpdateCommand = CommandList;
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Projects {
     pub directory: ProjectLocation,
+
+    /// Minijinja template for where a repository lives under `directory`,
+    /// with `provider`, `owner` and `repo`. Providers can set their own.
+    /// Default: "{{ provider }}/{{ owner }}/{{ repo }}"
+    #[serde(default)]
+    pub path_template: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -220,6 +669,10 @@ pub struct GitHub {
     pub users: Vec<GitHubUser>,
     #[serde(default)]
     pub organisations: Vec<GitHubOrganisation>,
+
+    /// Overrides `projects.path_template` for this provider's repositories.
+    #[serde(default)]
+    pub path_template: Option<String>,
 }
 
 /// Generates a newtype wrapper around `String` with `From` impls for owned and borrowed access.
@@ -259,6 +712,10 @@ pub struct Gitea {
     pub users: Vec<GiteaUser>,
     #[serde(default)]
     pub organisations: Vec<GiteaOrganisation>,
+
+    /// Overrides `projects.path_template` for this provider's repositories.
+    #[serde(default)]
+    pub path_template: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -293,7 +750,20 @@ impl Config {
     }
 
     pub fn from_string(content: &str) -> anyhow::Result<Config> {
-        toml::from_str(content).context("failed to deserialize config file")
+        let config: Config = toml::from_str(content).context("failed to deserialize config file")?;
+
+        // Catch template typos here rather than cloning to a fallback path.
+        let env = minijinja::Environment::new();
+        let path_templates = std::iter::once(&config.settings.projects.path_template)
+            .chain(config.providers.github.iter().map(|g| &g.path_template))
+            .chain(config.providers.gitea.iter().map(|g| &g.path_template))
+            .flatten();
+        for template in path_templates {
+            env.template_from_str(template)
+                .with_context(|| format!("invalid path_template: {template}"))?;
+        }
+
+        Ok(config)
     }
 }
 
@@ -348,7 +818,8 @@ mod test {
                             organisations: vec![GitHubOrganisation("lunarway".into())],
                             url: None,
                             access_token: GitHubAccessToken::Direct("some-token".into()),
-                            current_user: Some("kjuulh".into())
+                            current_user: Some("kjuulh".into()),
+                            path_template: None,
                         },
                         GitHub {
                             users: vec![GitHubUser("other".into())],
@@ -357,7 +828,8 @@ mod test {
                             access_token: GitHubAccessToken::Env {
                                 env: "something".into()
                             },
-                            current_user: None
+                            current_user: None,
+                            path_template: None,
                         }
                     ],
                     gitea: vec![
@@ -366,21 +838,24 @@ mod test {
                             users: vec![GiteaUser("kjuulh".into())],
                             organisations: vec![GiteaOrganisation("lunarway".into())],
                             access_token: None,
-                            current_user: Some("kjuulh".into())
+                            current_user: Some("kjuulh".into()),
+                            path_template: None,
                         },
                         Gitea {
                             url: "https://git.front.kjuulh.io/api/v1".into(),
                             users: vec![GiteaUser("other".into())],
                             organisations: vec![GiteaOrganisation("org".into())],
                             access_token: None,
-                            current_user: None
+                            current_user: None,
+                            path_template: None,
                         },
                         Gitea {
                             url: "https://git.front.kjuulh.io/api/v1".into(),
                             users: vec![],
                             organisations: vec![],
                             access_token: None,
-                            current_user: None
+                            current_user: None,
+                            path_template: None,
                         },
                     ]
                 },
@@ -394,13 +869,23 @@ mod test {
                         }
                     },
                     projects: Projects {
-                        directory: PathBuf::from("git").into()
+                        directory: PathBuf::from("git").into(),
+                        path_template: None,
                     },
+                    pre_clone_command: None,
                     post_update_command: None,
                     post_clone_command: None,
+                    on_enter_command: None,
                     clone_command: None,
+                    clone_profiles: Vec::new(),
                     worktree: None,
                     project: None,
+                    search: None,
+                    keybindings: None,
+                    ui: None,
+                    rules: Vec::new(),
+                    mirrors: None,
+                    update: None,
                 }
             },
             config
@@ -426,11 +911,20 @@ mod test {
                 settings: Settings {
                     cache: Cache::default(),
                     projects: Projects::default(),
+                    pre_clone_command: None,
                     post_update_command: None,
                     post_clone_command: None,
+                    on_enter_command: None,
                     clone_command: None,
+                    clone_profiles: Vec::new(),
                     worktree: None,
                     project: None,
+                    search: None,
+                    keybindings: None,
+                    ui: None,
+                    rules: Vec::new(),
+                    mirrors: None,
+                    update: None,
                 }
             },
             config
@@ -483,6 +977,7 @@ mod test {
                 list_branches_command: Some(
                     "jj -R {{ bare_path }} branch list".to_string()
                 ),
+                post_add_command: None,
             })
         );
 
@@ -505,4 +1000,211 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_can_parse_search_settings() -> anyhow::Result<()> {
+        let content = r#"
+              [settings.search]
+              frecency_weight = 2.5
+              on_ambiguous = "interactive"
+              ambiguity_threshold = 20
+            "#;
+
+        let config = Config::from_string(content)?;
+
+        assert_eq!(
+            config.settings.search,
+            Some(SearchSettings {
+                frecency_weight: Some(2.5),
+                on_ambiguous: Some(AmbiguityAction::Interactive),
+                ambiguity_threshold: Some(20),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_can_parse_keybindings() -> anyhow::Result<()> {
+        let content = r#"
+              [settings.keybindings]
+              delete_word = ["ctrl-w", "alt-backspace"]
+              toggle_preview = "ctrl-y"
+            "#;
+
+        let config = Config::from_string(content)?;
+        let keybindings = config.settings.keybindings.expect("keybindings to be set");
+
+        assert_eq!(
+            keybindings.get(&PickerAction::DeleteWord),
+            Some(&KeyBindingList::Multiple(vec![
+                "ctrl-w".parse()?,
+                "alt-backspace".parse()?
+            ]))
+        );
+        assert_eq!(
+            keybindings.get(&PickerAction::TogglePreview),
+            Some(&KeyBindingList::Single("ctrl-y".parse()?))
+        );
+
+        assert!(Config::from_string("[settings.keybindings]\nup = \"ctrl-nope\"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_can_parse_ui_settings() -> anyhow::Result<()> {
+        let content = r##"
+              [settings.ui]
+              highlight_symbol = "▶ "
+              list_direction = "top_to_bottom"
+              height = "40%"
+              border = true
+              title = "gitnow"
+
+              [settings.ui.colors]
+              prompt = "light_green"
+              matched = "#ff8700"
+            "##;
+
+        let config = Config::from_string(content)?;
+
+        assert_eq!(
+            config.settings.ui,
+            Some(UiSettings {
+                colors: Some(UiColors {
+                    prompt: Some(UiColor(ratatui::style::Color::LightGreen)),
+                    matched: Some(UiColor(ratatui::style::Color::Rgb(0xff, 0x87, 0x00))),
+                    ..Default::default()
+                }),
+                highlight_symbol: Some("▶ ".into()),
+                list_direction: Some(UiListDirection::TopToBottom),
+                height: Some(UiHeight::Percent(40)),
+                border: Some(true),
+                title: Some("gitnow".into()),
+            })
+        );
+
+        assert_eq!(
+            Config::from_string("[settings.ui]\nheight = 12")?
+                .settings
+                .ui
+                .and_then(|ui| ui.height),
+            Some(UiHeight::Lines(12))
+        );
+        assert!(Config::from_string("[settings.ui]\nheight = \"140%\"").is_err());
+        assert!(Config::from_string("[settings.ui.colors]\nprompt = \"nope\"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_can_parse_clone_profiles() -> anyhow::Result<()> {
+        let content = r#"
+              [[settings.clone_profiles]]
+              name = "monorepo"
+              repositories = ["github.com/bigcorp/*", "git.example.com/**"]
+              depth = 1
+              filter = "blob:none"
+              sparse_paths = ["services/api"]
+            "#;
+
+        let config = Config::from_string(content)?;
+
+        let profile = &config.settings.clone_profiles[0];
+        assert_eq!(profile.name, "monorepo");
+        assert_eq!(profile.depth, Some(1));
+        assert_eq!(profile.filter.as_deref(), Some("blob:none"));
+        assert_eq!(profile.sparse_paths, vec!["services/api".to_string()]);
+
+        let [owner, provider] = &profile.repositories[..] else {
+            panic!("expected two patterns");
+        };
+        assert!(owner.matches("github.com/bigcorp/monorepo"));
+        assert!(owner.matches("github.com/BigCorp/monorepo"));
+        assert!(!owner.matches("github.com/bigcorp/monorepo/nested"));
+        assert!(!owner.matches("github.com/bigcorpx/monorepo"));
+        assert!(provider.matches("git.example.com/team/repo"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_matching_rule_overrides_settings() -> anyhow::Result<()> {
+        let content = r#"
+              [settings]
+              clone_command = "git clone {{ ssh_url }} {{ path }}"
+              post_clone_command = "echo cloned"
+
+              [settings.worktree]
+              add_command = "git worktree add"
+
+              [[settings.rules]]
+              repositories = ["github.com/lunarway/*"]
+              clone_command = "jj git clone {{ ssh_url }} {{ path }}"
+              on_enter_command = "direnv allow"
+              worktree = { clone_command = "jj git clone {{ ssh_url }} {{ bare_path }}", post_add_command = "mise install" }
+
+              [[settings.rules]]
+              repositories = ['regex:^github\.com/.*-rs$']
+              post_clone_command = [
+                "cargo fetch",
+                { command = "cargo build", fail_on_error = true, timeout = 600 },
+              ]
+            "#;
+
+        let config = Config::from_string(content)?;
+        let repository = |owner: &str, repo_name: &str| Repository {
+            provider: "github.com".into(),
+            owner: owner.into(),
+            repo_name: repo_name.into(),
+            ..Default::default()
+        };
+
+        let settings = config.settings.for_repository(&repository("lunarway", "api-rs"));
+        assert_eq!(
+            settings.clone_command.as_deref(),
+            Some("jj git clone {{ ssh_url }} {{ path }}")
+        );
+        assert_eq!(
+            settings.post_clone_command,
+            Some(CommandList::Single("echo cloned".into()))
+        );
+        let worktree = settings.worktree.clone().unwrap();
+        assert_eq!(
+            worktree.clone_command.as_deref(),
+            Some("jj git clone {{ ssh_url }} {{ bare_path }}")
+        );
+        assert_eq!(worktree.add_command.as_deref(), Some("git worktree add"));
+        assert_eq!(
+            worktree.post_add_command,
+            Some(CommandList::Single("mise install".into()))
+        );
+        assert_eq!(
+            settings.on_enter_command,
+            Some(CommandList::Single("direnv allow".into()))
+        );
+
+        let settings = config.settings.for_repository(&repository("kjuulh", "gitnow-rs"));
+        assert_eq!(
+            settings.clone_command.as_deref(),
+            Some("git clone {{ ssh_url }} {{ path }}")
+        );
+        assert_eq!(
+            settings.post_clone_command,
+            Some(CommandList::Multiple(vec![
+                "cargo fetch".into(),
+                Hook::Detailed {
+                    command: "cargo build".into(),
+                    fail_on_error: true,
+                    timeout: Some(600),
+                },
+            ]))
+        );
+
+        let settings = config.settings.for_repository(&repository("kjuulh", "gitnow"));
+        assert!(matches!(settings, Cow::Borrowed(_)));
+
+        Ok(())
+    }
 }
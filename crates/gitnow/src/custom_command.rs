@@ -1,75 +1,446 @@
 use std::path::Path;
+use std::process::Stdio;
 
-use crate::{app::App, config::CommandList};
+use anyhow::Context;
+
+use crate::{
+    app::App,
+    config::{CommandList, Hook, ProjectSettings},
+    git_provider::Repository,
+    history::HistoryKind,
+};
 
 pub struct CustomCommand {
+    repository: Repository,
+    pre_clone: Option<CommandList>,
     post_clone: Option<CommandList>,
     post_update: Option<CommandList>,
+    post_worktree_add: Option<CommandList>,
+    on_enter: Option<CommandList>,
 }
 
 impl CustomCommand {
-    pub fn new(app: &App) -> Self {
+    /// Uses the hooks of the first rule matching `repository`, if any.
+    pub fn new(app: &App, repository: &Repository) -> Self {
+        let settings = app.config.settings.for_repository(repository);
+
         Self {
-            post_clone: app.config.settings.post_clone_command.clone(),
-            post_update: app.config.settings.post_update_command.clone(),
+            repository: repository.clone(),
+            pre_clone: settings.pre_clone_command.clone(),
+            post_clone: settings.post_clone_command.clone(),
+            post_update: settings.post_update_command.clone(),
+            post_worktree_add: settings
+                .worktree
+                .as_ref()
+                .and_then(|worktree| worktree.post_add_command.clone()),
+            on_enter: settings.on_enter_command.clone(),
         }
     }
 
-    async fn execute_commands(
-        commands: &CommandList,
-        path: &Path,
-        label: &str,
-    ) -> anyhow::Result<()> {
-        for command in commands.get_commands() {
-            let command_parts = command.split(' ').collect::<Vec<_>>();
-            let Some((first, rest)) = command_parts.split_first() else {
-                return Ok(());
-            };
-
-            let mut cmd = tokio::process::Command::new(first);
-            cmd.args(rest).current_dir(path);
-
-            eprintln!("running command: {}", command);
-
-            tracing::info!(
-                path = path.display().to_string(),
-                cmd = command,
-                "running custom {} command",
-                label
-            );
-            let output = cmd.output().await?;
-            let stdout = std::str::from_utf8(&output.stdout)?;
-            tracing::info!(
-                stdout = stdout,
-                "finished running custom {} command",
-                label
-            );
+    /// The variables every repository hook gets.
+    fn context(&self, path: &Path) -> minijinja::Value {
+        minijinja::context! {
+            path => path.display().to_string(),
+            repo => self.repository.repo_name,
+            owner => self.repository.owner,
+            provider => self.repository.provider,
+            ssh_url => self.repository.ssh_url,
         }
+    }
 
+    /// Runs in the directory that will hold the clone at `path`. A failure
+    /// with `fail_on_error` stops the clone.
+    pub async fn execute_pre_clone_command(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(ref cmds) = self.pre_clone
+            && let Some(parent) = path.parent()
+        {
+            tokio::fs::create_dir_all(parent).await?;
+            execute_commands(cmds, self.context(path), parent, "pre clone").await?;
+        }
         Ok(())
     }
 
     pub async fn execute_post_clone_command(&self, path: &Path) -> anyhow::Result<()> {
         if let Some(ref cmds) = self.post_clone {
-            Self::execute_commands(cmds, path, "post clone").await?;
+            execute_commands(cmds, self.context(path), path, "post clone").await?;
         }
         Ok(())
     }
 
     pub async fn execute_post_update_command(&self, path: &Path) -> anyhow::Result<()> {
         if let Some(ref cmds) = self.post_update {
-            Self::execute_commands(cmds, path, "post update").await?;
+            execute_commands(cmds, self.context(path), path, "post update").await?;
         }
         Ok(())
     }
+
+    pub async fn execute_post_worktree_add_command(
+        &self,
+        worktree_path: &Path,
+        bare_path: &Path,
+        branch: &str,
+    ) -> anyhow::Result<()> {
+        if let Some(ref cmds) = self.post_worktree_add {
+            let context = minijinja::context! {
+                branch => branch,
+                bare_path => bare_path.display().to_string(),
+                worktree_path => worktree_path.display().to_string(),
+                ..self.context(worktree_path)
+            };
+            execute_commands(cmds, context, worktree_path, "post worktree add").await?;
+        }
+        Ok(())
+    }
+
+    pub async fn execute_on_enter_command(
+        &self,
+        path: &Path,
+        kind: HistoryKind,
+    ) -> anyhow::Result<()> {
+        if let Some(ref cmds) = self.on_enter {
+            let context = minijinja::context! {
+                kind => kind.to_string(),
+                ..self.context(path)
+            };
+            execute_commands(cmds, context, path, "on enter").await?;
+        }
+        Ok(())
+    }
+}
+
+/// Hooks around project changes, from `[settings.project]`.
+pub struct ProjectHooks {
+    settings: Option<ProjectSettings>,
+    on_enter: Option<CommandList>,
+}
+
+impl ProjectHooks {
+    pub fn new(app: &App) -> Self {
+        Self {
+            settings: app.config.settings.project.clone(),
+            on_enter: app.config.settings.on_enter_command.clone(),
+        }
+    }
+
+    /// `repos` are `provider/owner/repo` paths of the repositories the
+    /// change is about.
+    fn context(name: &str, path: &Path, repos: &[String]) -> minijinja::Value {
+        minijinja::context! {
+            project => name,
+            path => path.display().to_string(),
+            repos => repos,
+        }
+    }
+
+    async fn execute(
+        &self,
+        hook: fn(&ProjectSettings) -> Option<&CommandList>,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+        cwd: &Path,
+        label: &str,
+    ) -> anyhow::Result<()> {
+        if let Some(cmds) = self.settings.as_ref().and_then(hook) {
+            execute_commands(cmds, Self::context(name, path, repos), cwd, label).await?;
+        }
+        Ok(())
+    }
+
+    /// Runs in the projects directory before the project at `path` exists.
+    /// A failure with `fail_on_error` stops the creation.
+    pub async fn execute_pre_create_command(
+        &self,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+    ) -> anyhow::Result<()> {
+        let cwd = path.parent().unwrap_or(path);
+        tokio::fs::create_dir_all(cwd).await?;
+        self.execute(
+            |s| s.pre_create_command.as_ref(),
+            name,
+            path,
+            repos,
+            cwd,
+            "pre project create",
+        )
+        .await
+    }
+
+    pub async fn execute_post_create_command(
+        &self,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+    ) -> anyhow::Result<()> {
+        self.execute(
+            |s| s.post_create_command.as_ref(),
+            name,
+            path,
+            repos,
+            path,
+            "post project create",
+        )
+        .await
+    }
+
+    pub async fn execute_post_add_command(
+        &self,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+    ) -> anyhow::Result<()> {
+        self.execute(
+            |s| s.post_add_command.as_ref(),
+            name,
+            path,
+            repos,
+            path,
+            "post project add",
+        )
+        .await
+    }
+
+    pub async fn execute_post_remove_command(
+        &self,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+    ) -> anyhow::Result<()> {
+        self.execute(
+            |s| s.post_remove_command.as_ref(),
+            name,
+            path,
+            repos,
+            path,
+            "post project remove",
+        )
+        .await
+    }
+
+    /// A failure with `fail_on_error` keeps the project.
+    pub async fn execute_pre_delete_command(
+        &self,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+    ) -> anyhow::Result<()> {
+        self.execute(
+            |s| s.pre_delete_command.as_ref(),
+            name,
+            path,
+            repos,
+            path,
+            "pre project delete",
+        )
+        .await
+    }
+
+    /// Runs in the projects directory once the project is gone.
+    pub async fn execute_post_delete_command(
+        &self,
+        name: &str,
+        path: &Path,
+        repos: &[String],
+    ) -> anyhow::Result<()> {
+        self.execute(
+            |s| s.post_delete_command.as_ref(),
+            name,
+            path,
+            repos,
+            path.parent().unwrap_or(path),
+            "post project delete",
+        )
+        .await
+    }
+
+    pub async fn execute_on_enter_command(&self, name: &str, path: &Path) -> anyhow::Result<()> {
+        if let Some(ref cmds) = self.on_enter {
+            let context = minijinja::context! {
+                kind => HistoryKind::Project.to_string(),
+                ..Self::context(name, path, &[])
+            };
+            execute_commands(cmds, context, path, "on enter").await?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every hook in order. A failing hook is reported and the rest still
+/// run, unless it sets `fail_on_error`.
+async fn execute_commands(
+    commands: &CommandList,
+    context: minijinja::Value,
+    path: &Path,
+    label: &str,
+) -> anyhow::Result<()> {
+    for hook in commands.get_commands() {
+        if let Err(e) = execute_hook(&hook, &context, path, label).await {
+            if hook.fail_on_error() {
+                return Err(e);
+            }
+            eprintln!("warning: {e:#}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Shell-quotes a value, or each item of a list, so paths with spaces or
+/// quotes stay one argument: `cd {{ path | quote }}`.
+fn quote(value: minijinja::Value) -> String {
+    if value.kind() == minijinja::value::ValueKind::Seq
+        && let Ok(items) = value.try_iter()
+    {
+        return items
+            .map(|item| shell_words::quote(&item.to_string()).into_owned())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    shell_words::quote(&value.to_string()).into_owned()
+}
+
+/// Renders `hook` with `context` and runs it with `$SHELL -c` in `path`, so
+/// quoting, pipes, `&&` and environment variables work as in a terminal.
+/// Variables are inserted as they are, unless quoted with the `quote` filter.
+async fn execute_hook(
+    hook: &Hook,
+    context: &minijinja::Value,
+    path: &Path,
+    label: &str,
+) -> anyhow::Result<()> {
+    let mut env = minijinja::Environment::new();
+    env.add_filter("quote", quote);
+    let command = env
+        .render_str(hook.command(), context)
+        .with_context(|| format!("failed to render {label} command: {}", hook.command()))?;
+    let shell = user_shell();
+
+    eprintln!("running command: {}", command);
+    tracing::info!(
+        path = path.display().to_string(),
+        cmd = command,
+        "running custom {} command",
+        label
+    );
+
+    let output = tokio::process::Command::new(&shell)
+        .arg("-c")
+        .arg(&command)
+        .current_dir(path)
+        .stdin(Stdio::null())
+        // A hook that times out is killed along with its future.
+        .kill_on_drop(true)
+        .output();
+    let output = match hook.timeout() {
+        Some(timeout) => tokio::time::timeout(timeout, output)
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "{label} command timed out after {}s: {command}",
+                    timeout.as_secs()
+                )
+            })?,
+        None => output.await,
+    }
+    .with_context(|| format!("failed to run {label} command with {shell}: {command}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    tracing::info!(
+        stdout = stdout.as_ref(),
+        "finished running custom {} command",
+        label
+    );
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut message = format!("{label} command failed ({}): {command}", output.status);
+        if !stderr.trim().is_empty() {
+            message.push('\n');
+            message.push_str(stderr.trim_end());
+        }
+        anyhow::bail!(message);
+    }
+
+    Ok(())
+}
+
+/// `$SHELL`, or `sh` if it isn't set.
+pub fn user_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "sh".into())
 }
 
 pub trait CustomCommandApp {
-    fn custom_command(&self) -> CustomCommand;
+    fn custom_command(&self, repository: &Repository) -> CustomCommand;
+    fn project_hooks(&self) -> ProjectHooks;
 }
 
 impl CustomCommandApp for App {
-    fn custom_command(&self) -> CustomCommand {
-        CustomCommand::new(self)
+    fn custom_command(&self, repository: &Repository) -> CustomCommand {
+        CustomCommand::new(self, repository)
+    }
+
+    fn project_hooks(&self) -> ProjectHooks {
+        ProjectHooks::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn runs_hooks_through_a_shell_with_template_variables() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("gitnow-hook-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let context = minijinja::context! { repo => "gitnow" };
+
+        let hook = Hook::from("echo '{{ repo }}' | tr a-z A-Z > out && test -s out");
+        execute_hook(&hook, &context, &dir, "post clone").await?;
+        assert_eq!(tokio::fs::read_to_string(dir.join("out")).await?, "GITNOW\n");
+
+        let failing = Hook::from("echo broken >&2; exit 3");
+        let error = execute_hook(&failing, &context, &dir, "post clone")
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("broken"));
+
+        let slow = Hook::Detailed {
+            command: "sleep 5".into(),
+            fail_on_error: true,
+            timeout: Some(0),
+        };
+        let error = execute_hook(&slow, &context, &dir, "post clone")
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("timed out"));
+
+        tokio::fs::remove_dir_all(&dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quote_filter_keeps_variables_one_argument() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("gitnow-hook-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let context = minijinja::context! {
+            path => "/tmp/my repo's; rm -rf x",
+            repos => vec!["github.com/kjuulh/a b", "github.com/kjuulh/c"],
+        };
+
+        let hook = Hook::from("printf '%s\\n' {{ path | quote }} {{ repos | quote }} > out");
+        execute_hook(&hook, &context, &dir, "post create").await?;
+        assert_eq!(
+            tokio::fs::read_to_string(dir.join("out")).await?,
+            "/tmp/my repo's; rm -rf x\ngithub.com/kjuulh/a b\ngithub.com/kjuulh/c\n"
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+        Ok(())
     }
 }
@@ -1,25 +1,102 @@
-use nucleo_matcher::{pattern::Pattern, Matcher};
+use nucleo_matcher::{pattern::Pattern, Matcher, Utf32Str};
 
-use crate::app::App;
+use crate::{
+    app::App,
+    history::{Frecency, HistoryApp},
+    interactive::Searchable,
+    layout::PathLayoutApp,
+    query::{Query, QueryContext},
+};
 
-pub struct FuzzyMatcher {}
+#[derive(Default)]
+pub struct FuzzyMatcher {
+    frecency: Frecency,
+    context: QueryContext,
+}
 
 impl FuzzyMatcher {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Blends the given frecency bonuses into the match ordering.
+    pub fn with_frecency(mut self, frecency: Frecency) -> Self {
+        self.frecency = frecency;
+        self
+    }
+
+    pub fn with_context(mut self, context: QueryContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn context(&self) -> &QueryContext {
+        &self.context
     }
 
-    pub fn match_pattern<'a>(&self, pattern: &'a str, items: &'a [&'a str]) -> Vec<&'a str> {
-        let pat = Pattern::new(
-            pattern,
+    /// Filters `items` by the qualifiers in `search` and fuzzy-matches the
+    /// remaining text against their labels. Returns the matches with their
+    /// score, best match first. The score is the nucleo match score plus any
+    /// frecency bonus.
+    pub fn match_items<'a, T: Searchable>(&self, search: &str, items: &'a [T]) -> Vec<(&'a T, u32)> {
+        self.score_items(search, items, false)
+            .into_iter()
+            .map(|(item, score, _)| (item, score))
+            .collect()
+    }
+
+    /// Like [`FuzzyMatcher::match_items`], but also returns the sorted char
+    /// indices of each label that matched, for highlighting.
+    pub fn match_items_highlighted<'a, T: Searchable>(
+        &self,
+        search: &str,
+        items: &'a [T],
+    ) -> Vec<(&'a T, u32, Vec<u32>)> {
+        self.score_items(search, items, true)
+    }
+
+    fn score_items<'a, T: Searchable>(
+        &self,
+        search: &str,
+        items: &'a [T],
+        with_indices: bool,
+    ) -> Vec<(&'a T, u32, Vec<u32>)> {
+        let query = Query::parse(search);
+        let pattern = Pattern::parse(
+            &query.text,
             nucleo_matcher::pattern::CaseMatching::Ignore,
             nucleo_matcher::pattern::Normalization::Smart,
-            nucleo_matcher::pattern::AtomKind::Fuzzy,
         );
         let mut matcher = Matcher::new(nucleo_matcher::Config::DEFAULT);
-        let res = pat.match_list(items, &mut matcher);
+        let mut buf = Vec::new();
+
+        let mut res = items
+            .iter()
+            .filter(|item| {
+                query
+                    .qualifiers
+                    .iter()
+                    .all(|q| item.matches_qualifier(q, &self.context))
+            })
+            .filter_map(|item| {
+                let label = item.display_label();
+                let haystack = Utf32Str::new(&label, &mut buf);
+                let mut indices = Vec::new();
+                let score = if with_indices {
+                    let score = pattern.indices(haystack, &mut matcher, &mut indices)?;
+                    indices.sort_unstable();
+                    indices.dedup();
+                    score
+                } else {
+                    pattern.score(haystack, &mut matcher)?
+                };
+                Some((item, score + self.frecency.bonus(&label), indices))
+            })
+            .collect::<Vec<_>>();
+
+        res.sort_by_key(|(_, score, _)| std::cmp::Reverse(*score));
 
-        res.into_iter().map(|(item, _)| *item).collect::<Vec<_>>()
+        res
     }
 }
 
@@ -30,5 +107,9 @@ pub trait FuzzyMatcherApp {
 impl FuzzyMatcherApp for &'static App {
     fn fuzzy_matcher(&self) -> FuzzyMatcher {
         FuzzyMatcher::new()
+            .with_frecency(self.history().frecency())
+            .with_context(QueryContext {
+                layout: self.layout(),
+            })
     }
 }
@@ -17,5 +17,20 @@ pub struct Repository {
     pub repo_name: ::prost::alloc::string::String,
     #[prost(string, tag="4")]
     pub ssh_url: ::prost::alloc::string::String,
+    #[prost(uint64, tag="5")]
+    pub id: u64,
+    #[prost(string, tag="6")]
+    pub language: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="7")]
+    pub topics: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bool, tag="8")]
+    pub archived: bool,
+    #[prost(string, tag="9")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, tag="10")]
+    pub default_branch: ::prost::alloc::string::String,
+    /// Unix seconds, 0 when unknown.
+    #[prost(int64, tag="11")]
+    pub pushed_at: i64,
 }
 // @@protoc_insertion_point(module)
@@ -1,15 +1,74 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::{app::App, git_provider::Repository, template_command};
+use crate::{
+    app::App,
+    components::progress_bar::{Progress, ProgressSender},
+    config::CloneProfile,
+    git_provider::Repository,
+    layout::PathLayoutApp,
+    mirror::MirrorsApp,
+    template_command,
+};
+
+/// A clone command that ran but exited unsuccessfully.
+#[derive(Debug)]
+pub struct CloneError {
+    /// `provider/owner/repo` of the repository being cloned.
+    pub repository: String,
+    /// The rendered clone command.
+    pub command: String,
+    /// `None` when the command was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+}
+
+impl std::fmt::Display for CloneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to clone {}: `{}` ", self.repository, self.command)?;
+        match self.exit_code {
+            Some(code) => write!(f, "exited with code {code}")?,
+            None => write!(f, "was killed by a signal")?,
+        }
+
+        let stderr = self.stderr.trim();
+        if !stderr.is_empty() {
+            write!(f, ": {stderr}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for CloneError {}
 
 #[derive(Debug, Clone)]
 pub struct GitClone {
     app: &'static App,
+    progress: Option<ProgressSender>,
+    profile: Option<String>,
 }
 
 impl GitClone {
     pub fn new(app: &'static App) -> Self {
-        Self { app }
+        Self {
+            app,
+            progress: None,
+            profile: None,
+        }
+    }
+
+    /// Clones with the named profile instead of the one matching each
+    /// repository.
+    pub fn with_profile(mut self, profile: Option<String>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Reports the clone command's progress to `progress`.
+    pub fn with_progress(mut self, progress: ProgressSender) -> Self {
+        self.progress = Some(progress);
+        self
     }
 
     pub async fn clone_repo(
@@ -17,15 +76,9 @@ impl GitClone {
         repository: &Repository,
         force_refresh: bool,
     ) -> anyhow::Result<()> {
-        let project_path = self
-            .app
-            .config
-            .settings
-            .projects
-            .directory
-            .join(repository.to_rel_path());
+        let project_path = self.app.layout().path(repository);
 
-        if force_refresh {
+        if force_refresh && project_path.exists() {
             tokio::fs::remove_dir_all(&project_path).await?;
         }
 
@@ -37,50 +90,248 @@ impl GitClone {
             return Ok(());
         }
 
-        let template = self
-            .app
-            .config
-            .settings
-            .clone_command
-            .as_deref()
-            .unwrap_or(template_command::DEFAULT_CLONE_COMMAND);
+        self.clone_into(repository, &project_path).await
+    }
+
+    /// Clones `repository` into `path` with the configured clone command.
+    pub async fn clone_into(&self, repository: &Repository, path: &Path) -> anyhow::Result<()> {
+        let settings = self.app.config.settings.for_repository(repository);
+        let custom_template = settings.clone_command.as_deref();
+        let template = custom_template.unwrap_or(template_command::DEFAULT_CLONE_COMMAND);
+        let profile = clone_profile(self.app, repository, self.profile.as_deref())?;
 
         tracing::info!(
             "cloning: {} into {}",
             repository.ssh_url.as_str(),
-            &project_path.display().to_string(),
+            &path.display().to_string(),
         );
 
-        let path_str = project_path.display().to_string();
-        let context = HashMap::from([
+        let mirror = self
+            .app
+            .mirrors()
+            .ensure(repository, profile, self.progress.as_ref())
+            .await;
+
+        let path_str = path.display().to_string();
+        let profile_vars = clone_template_vars(profile, mirror.as_deref(), true);
+        let mut context = HashMap::from([
             ("ssh_url", repository.ssh_url.as_str()),
             ("path", path_str.as_str()),
         ]);
+        context.extend(profile_vars.iter().map(|(k, v)| (*k, v.as_str())));
+
+        run_clone_command(
+            repository,
+            template,
+            context.clone(),
+            path,
+            self.progress.as_ref(),
+        )
+        .await?;
 
-        let output = template_command::render_and_execute(template, context).await?;
-        match output.status.success() {
-            true => tracing::debug!(
-                "cloned {} into {}",
-                repository.ssh_url.as_str(),
-                &project_path.display().to_string(),
-            ),
-            false => {
-                let stdout = std::str::from_utf8(&output.stdout).unwrap_or_default();
-                let stderr = std::str::from_utf8(&output.stderr).unwrap_or_default();
-                tracing::error!(
-                    "failed to clone {} into {}, with output: {}, err: {}",
-                    repository.ssh_url.as_str(),
-                    &project_path.display().to_string(),
-                    stdout,
-                    stderr
-                )
+        // Custom clone commands get `sparse_paths` and set up the sparse
+        // checkout themselves.
+        if custom_template.is_none()
+            && profile.is_some_and(|profile| !profile.sparse_paths.is_empty())
+        {
+            let output = template_command::render_and_execute(
+                template_command::DEFAULT_SPARSE_CHECKOUT_COMMAND,
+                context,
+            )
+            .await?;
+            if !output.status.success() {
+                tokio::fs::remove_dir_all(path).await.ok();
+                anyhow::bail!(
+                    "failed to set sparse checkout paths for {}: {}",
+                    repository.to_rel_path().display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
             }
         }
 
+        tracing::debug!(
+            "cloned {} into {}",
+            repository.ssh_url.as_str(),
+            &path.display().to_string(),
+        );
+
         Ok(())
     }
 }
 
+/// The clone profile for `repository`: the profile called `name` if given,
+/// otherwise the first one whose patterns match the repository.
+pub fn clone_profile<'a>(
+    app: &'a App,
+    repository: &Repository,
+    name: Option<&str>,
+) -> anyhow::Result<Option<&'a CloneProfile>> {
+    let profiles = &app.config.settings.clone_profiles;
+
+    if let Some(name) = name {
+        return find_clone_profile(app, name).map(Some);
+    }
+
+    let rel_path = repository.to_rel_path().display().to_string();
+    Ok(profiles
+        .iter()
+        .find(|profile| profile.repositories.iter().any(|p| p.matches(&rel_path))))
+}
+
+/// Looks up a profile from `settings.clone_profiles` by name.
+pub fn find_clone_profile<'a>(app: &'a App, name: &str) -> anyhow::Result<&'a CloneProfile> {
+    app.config
+        .settings
+        .clone_profiles
+        .iter()
+        .find(|profile| profile.name == name)
+        .ok_or_else(|| anyhow::anyhow!("unknown clone profile: {name}"))
+}
+
+/// Template variables for a clone with `profile`, borrowing objects from
+/// `mirror`. `clone_args` holds the matching git options, including
+/// `--sparse` when `sparse` is set and the profile has sparse paths.
+pub fn clone_template_vars(
+    profile: Option<&CloneProfile>,
+    mirror: Option<&Path>,
+    sparse: bool,
+) -> Vec<(&'static str, String)> {
+    let mut args = Vec::new();
+    let mut vars = Vec::new();
+
+    if let Some(mirror) = mirror {
+        let mirror = mirror.display().to_string();
+        args.extend([
+            "--reference-if-able".to_string(),
+            mirror.clone(),
+            "--dissociate".to_string(),
+        ]);
+        vars.push(("mirror_path", mirror));
+    }
+
+    let Some(profile) = profile else {
+        vars.push(("clone_args", shell_words::join(&args)));
+        return vars;
+    };
+
+    vars.push(("profile", profile.name.clone()));
+    if let Some(depth) = profile.depth {
+        args.extend(["--depth".to_string(), depth.to_string()]);
+        vars.push(("depth", depth.to_string()));
+    }
+    if let Some(filter) = &profile.filter {
+        args.extend(["--filter".to_string(), filter.clone()]);
+        vars.push(("filter", filter.clone()));
+    }
+    if !profile.sparse_paths.is_empty() {
+        if sparse {
+            args.push("--sparse".to_string());
+        }
+        vars.push(("sparse_paths", shell_words::join(&profile.sparse_paths)));
+    }
+    vars.push(("clone_args", shell_words::join(&args)));
+
+    vars
+}
+
+/// Runs a rendered clone command which creates `path`. Fails with a
+/// [`CloneError`] if the command exits unsuccessfully, and removes whatever
+/// it left at `path` if it fails or is cancelled. Each line the command
+/// writes to stderr is parsed with [`parse_progress`] and sent to `progress`.
+pub async fn run_clone_command(
+    repository: &Repository,
+    template: &str,
+    context: HashMap<&str, &str>,
+    path: &Path,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<()> {
+    let command = template_command::render_command_line(template, &context)?;
+    let cleanup = PartialClone::new(path);
+
+    let output = template_command::render_and_execute_with_progress(template, context, |line| {
+        if let Some(progress) = progress {
+            // The receiver is gone once the spinner has closed, which is fine.
+            let _ = progress.send(parse_progress(line));
+        }
+    })
+    .await?;
+    if !output.status.success() {
+        // Progress lines only repeat themselves, keep the actual messages.
+        let stderr = String::from_utf8_lossy(&output.stderr)
+            .split(['\n', '\r'])
+            .filter(|line| matches!(parse_progress(line), Progress::Message(_)))
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        return Err(CloneError {
+            repository: repository.to_rel_path().display().to_string(),
+            command,
+            exit_code: output.status.code(),
+            stderr,
+        }
+        .into());
+    }
+
+    cleanup.keep();
+    Ok(())
+}
+
+/// Parses a line of git's progress output, e.g.
+/// `remote: Counting objects:  45% (9/20)` or `Resolving deltas: 100% (3/3), done.`
+/// Anything else, including output from other clone tools, is returned as a
+/// [`Progress::Message`].
+pub fn parse_progress(line: &str) -> Progress {
+    let line = line.trim();
+    let stripped = line.strip_prefix("remote: ").unwrap_or(line);
+
+    if let Some((label, rest)) = stripped.split_once(':')
+        && !label.is_empty()
+        && label.chars().all(|c| c.is_alphabetic() || c == ' ')
+        && let Some((percent, counts)) = rest.trim_start().split_once('%')
+        && let Ok(percent) = percent.parse::<u8>()
+        && (counts.is_empty() || counts.starts_with(" ("))
+    {
+        return Progress::Percent {
+            label: label.to_string(),
+            percent,
+        };
+    }
+
+    Progress::Message(line.to_string())
+}
+
+/// Removes a clone target on drop unless [`PartialClone::keep`] was called.
+/// Paths that existed beforehand are never touched.
+struct PartialClone {
+    path: Option<PathBuf>,
+}
+
+impl PartialClone {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: (!path.exists()).then(|| path.to_path_buf()),
+        }
+    }
+
+    fn keep(mut self) {
+        self.path = None;
+    }
+}
+
+impl Drop for PartialClone {
+    fn drop(&mut self) {
+        let Some(path) = self.path.take().filter(|path| path.exists()) else {
+            return;
+        };
+
+        tracing::debug!("removing partial clone at {}", path.display());
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            tracing::warn!("failed to remove partial clone at {}: {e}", path.display());
+        }
+    }
+}
+
 pub trait GitCloneApp {
     fn git_clone(&self) -> GitClone;
 }
@@ -90,3 +341,98 @@ impl GitCloneApp for &'static App {
         GitClone::new(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_clone_returns_clone_error_and_cleans_up() {
+        let path = std::env::temp_dir().join(format!("gitnow-clone-{}", uuid::Uuid::new_v4()));
+        let path_str = path.display().to_string();
+        let repository = Repository {
+            provider: "github.com".into(),
+            owner: "kjuulh".into(),
+            repo_name: "gitnow".into(),
+            ..Default::default()
+        };
+
+        let err = run_clone_command(
+            &repository,
+            "sh -c 'mkdir -p {{ path }} && echo denied >&2 && exit 3'",
+            HashMap::from([("path", path_str.as_str())]),
+            &path,
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        let err = err.downcast::<CloneError>().unwrap();
+        assert_eq!(err.repository, "github.com/kjuulh/gitnow");
+        assert_eq!(err.exit_code, Some(3));
+        assert_eq!(err.stderr.trim(), "denied");
+        assert!(err.command.starts_with("sh -c 'mkdir -p "));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn parses_git_progress_lines() {
+        assert_eq!(
+            parse_progress("Receiving objects:  45% (450/1000), 1.20 MiB | 2.40 MiB/s"),
+            Progress::Percent {
+                label: "Receiving objects".into(),
+                percent: 45
+            }
+        );
+        assert_eq!(
+            parse_progress("remote: Counting objects: 100% (20/20), done."),
+            Progress::Percent {
+                label: "Counting objects".into(),
+                percent: 100
+            }
+        );
+        assert_eq!(
+            parse_progress("Cloning into 'gitnow'..."),
+            Progress::Message("Cloning into 'gitnow'...".into())
+        );
+        assert_eq!(
+            parse_progress("error: 100% sure this failed"),
+            Progress::Message("error: 100% sure this failed".into())
+        );
+    }
+
+    #[test]
+    fn clone_template_vars_borrow_from_the_mirror() {
+        let mirror = Path::new("/cache/mirrors/github.com/kjuulh/gitnow.git");
+        assert_eq!(
+            clone_template_vars(None, Some(mirror), true),
+            vec![
+                ("mirror_path", mirror.display().to_string()),
+                (
+                    "clone_args",
+                    format!("--reference-if-able {} --dissociate", mirror.display())
+                ),
+            ]
+        );
+
+        let profile = CloneProfile {
+            name: "sparse".into(),
+            repositories: Vec::new(),
+            depth: None,
+            filter: None,
+            sparse_paths: vec!["services/api".into()],
+        };
+        assert_eq!(
+            clone_template_vars(Some(&profile), Some(mirror), true),
+            vec![
+                ("mirror_path", mirror.display().to_string()),
+                ("profile", "sparse".into()),
+                ("sparse_paths", "services/api".into()),
+                (
+                    "clone_args",
+                    format!("--reference-if-able {} --dissociate --sparse", mirror.display())
+                ),
+            ]
+        );
+    }
+}
@@ -1,11 +1,24 @@
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
 pub struct Repository {
     pub provider: String,
     pub owner: String,
     pub repo_name: String,
     pub ssh_url: String,
+    /// Provider-assigned identifier, stable across renames and transfers.
+    /// Zero when unknown, e.g. for caches written by older versions.
+    pub id: u64,
+    /// Primary language as reported by the provider.
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub archived: bool,
+    pub description: Option<String>,
+    pub default_branch: Option<String>,
+    /// When the repository last received a push.
+    pub pushed_at: Option<DateTime<Utc>>,
 }
 
 impl Repository {
@@ -6,9 +6,23 @@ use crate::{app::App, config::GiteaAccessToken};
 
 #[derive(Debug, Deserialize)]
 struct GiteaRepo {
+    id: Option<u64>,
     name: Option<String>,
     ssh_url: Option<String>,
     owner: Option<GiteaUser>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    topics: Option<Vec<String>>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    default_branch: Option<String>,
+    /// Gitea doesn't expose the last push, so the last update stands in.
+    #[serde(default)]
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -173,6 +187,13 @@ fn to_repositories(provider: &str, repos: Vec<GiteaRepo>) -> Vec<super::Reposito
             ssh_url: repo
                 .ssh_url
                 .expect("ssh url to be set for a gitea repository"),
+            id: repo.id.unwrap_or_default(),
+            language: repo.language.filter(|l| !l.is_empty()),
+            topics: repo.topics.unwrap_or_default(),
+            archived: repo.archived,
+            description: repo.description.filter(|d| !d.is_empty()),
+            default_branch: repo.default_branch.filter(|b| !b.is_empty()),
+            pushed_at: repo.updated_at,
         })
         .collect()
 }
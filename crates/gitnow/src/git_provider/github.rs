@@ -5,9 +5,22 @@ use crate::{app::App, config::GitHubAccessToken};
 
 #[derive(Deserialize)]
 struct GitHubRepo {
+    id: u64,
     name: String,
     owner: Option<GitHubOwner>,
     ssh_url: Option<String>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    default_branch: Option<String>,
+    #[serde(default)]
+    pushed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Deserialize)]
@@ -127,6 +140,13 @@ impl GitHubProvider {
                     owner: repo.owner.map(|o| o.login)?,
                     repo_name: repo.name,
                     ssh_url: repo.ssh_url?,
+                    id: repo.id,
+                    language: repo.language,
+                    topics: repo.topics,
+                    archived: repo.archived,
+                    description: repo.description.filter(|d| !d.is_empty()),
+                    default_branch: repo.default_branch,
+                    pushed_at: repo.pushed_at,
                 })
             })
             .collect()
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::SystemTime;
+
+use anyhow::Context;
+
+/// How git and the usual forges report a remote repository that doesn't
+/// exist, lowercased. Git over https says `repository '<url>' not found`.
+const REMOTE_NOT_FOUND: &[&str] = &[
+    "repository not found",
+    "' not found",
+    "does not appear to be a git repository",
+    "does not exist",
+    "could not be found",
+    "cannot find repository",
+];
+
+/// Local state of a checkout, from `git status --porcelain=v2`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RepoStatus {
+    /// The checked out branch, `None` when HEAD is detached.
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    /// Commits on the branch that its upstream doesn't have.
+    pub ahead: u32,
+    /// Commits on the upstream that the branch doesn't have.
+    pub behind: u32,
+    /// Changed, staged, conflicted and untracked files.
+    pub changes: u32,
+    pub stashes: u32,
+}
+
+impl RepoStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.changes > 0
+    }
+
+    pub fn has_diverged(&self) -> bool {
+        self.ahead > 0 && self.behind > 0
+    }
+
+    fn parse(output: &str) -> Self {
+        let mut status = RepoStatus::default();
+        for line in output.lines() {
+            let Some(header) = line.strip_prefix("# ") else {
+                if !line.is_empty() {
+                    status.changes += 1;
+                }
+                continue;
+            };
+
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.head" if value != "(detached)" => status.branch = Some(value.into()),
+                "branch.upstream" => status.upstream = Some(value.into()),
+                "branch.ab" => {
+                    for count in value.split(' ') {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            status.ahead = ahead.parse().unwrap_or_default();
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            status.behind = behind.parse().unwrap_or_default();
+                        }
+                    }
+                }
+                "stash" => status.stashes = value.parse().unwrap_or_default(),
+                _ => {}
+            }
+        }
+
+        status
+    }
+}
+
+/// Runs git in a local clone. Git never prompts for credentials, so a
+/// missing login fails instead of hanging.
+pub struct GitRepo {
+    path: PathBuf,
+}
+
+impl GitRepo {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    async fn git(&self, args: &[&str]) -> anyhow::Result<String> {
+        let output = tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .context("failed to run git")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub async fn status(&self) -> anyhow::Result<RepoStatus> {
+        let output = self
+            .git(&["status", "--porcelain=v2", "--branch", "--show-stash"])
+            .await?;
+
+        Ok(RepoStatus::parse(&output))
+    }
+
+    pub async fn fetch(&self) -> anyhow::Result<()> {
+        self.git(&["fetch", "--quiet"]).await.map(|_| ())
+    }
+
+    /// Fetches every branch of `origin` into `refs/remotes/origin/*`. Bare
+    /// clones have no fetch refspec, and fetching into their local branches
+    /// would clash with the branches checked out in worktrees.
+    ///
+    /// Worktrees are added from, and branches listed as, local branches, so
+    /// afterwards new upstream branches get a local one, and local branches
+    /// that no worktree has checked out are fast-forwarded.
+    pub async fn fetch_bare(&self) -> anyhow::Result<()> {
+        self.git(&[
+            "fetch",
+            "--quiet",
+            "--prune",
+            "origin",
+            "+refs/heads/*:refs/remotes/origin/*",
+        ])
+        .await?;
+
+        let local = self
+            .git(&[
+                "for-each-ref",
+                "--format=%(refname:lstrip=2) %(objectname) %(worktreepath)",
+                "refs/heads",
+            ])
+            .await?;
+        let local: HashMap<&str, (&str, bool)> = local
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ' ');
+                let branch = fields.next()?;
+                let commit = fields.next()?;
+                let checked_out = fields.next().is_some_and(|path| !path.is_empty());
+                Some((branch, (commit, checked_out)))
+            })
+            .collect();
+
+        let remote = self
+            .git(&[
+                "for-each-ref",
+                "--format=%(refname:lstrip=3) %(objectname)",
+                "refs/remotes/origin",
+            ])
+            .await?;
+        for (branch, commit) in remote.lines().filter_map(|line| line.split_once(' ')) {
+            if branch == "HEAD" {
+                continue;
+            }
+            let head = format!("refs/heads/{branch}");
+            match local.get(branch) {
+                None => {
+                    self.git(&["update-ref", &head, commit, ""]).await?;
+                }
+                Some((current, false))
+                    if *current != commit
+                        && self
+                            .git(&["merge-base", "--is-ancestor", current, commit])
+                            .await
+                            .is_ok() =>
+                {
+                    self.git(&["update-ref", &head, commit, current]).await?;
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fast-forwards the checked out branch to its upstream if it is clean,
+    /// behind and hasn't diverged. Returns the status from before.
+    pub async fn fast_forward(&self) -> anyhow::Result<RepoStatus> {
+        let status = self.status().await?;
+        if status.upstream.is_some()
+            && status.behind > 0
+            && status.ahead == 0
+            && !status.is_dirty()
+        {
+            self.git(&["merge", "--ff-only", "--quiet", "@{upstream}"])
+                .await?;
+        }
+
+        Ok(status)
+    }
+
+    /// Commits on local branches that no remote-tracking branch has.
+    pub async fn unpushed_commits(&self) -> anyhow::Result<u32> {
+        let output = self
+            .git(&["rev-list", "--count", "--branches", "--not", "--remotes"])
+            .await?;
+
+        output
+            .trim()
+            .parse()
+            .with_context(|| format!("unexpected rev-list output: {output}"))
+    }
+
+    /// The URL of the `origin` remote.
+    pub async fn origin_url(&self) -> anyhow::Result<String> {
+        let output = self.git(&["remote", "get-url", "origin"]).await?;
+
+        Ok(output.trim().to_string())
+    }
+
+    /// Whether `origin` still resolves to a repository. `Ok(false)` only when
+    /// the remote says it doesn't exist; other failures, like being offline or
+    /// a missing login, are errors.
+    pub async fn origin_exists(&self) -> anyhow::Result<bool> {
+        match self.git(&["ls-remote", "origin", "HEAD"]).await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let message = format!("{e:#}").to_lowercase();
+                if REMOTE_NOT_FOUND.iter().any(|marker| message.contains(marker)) {
+                    Ok(false)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// When the repository was last fetched, from the age of `FETCH_HEAD`.
+    /// `None` if it never was.
+    pub async fn last_fetch(&self) -> Option<SystemTime> {
+        self.modified("FETCH_HEAD").await
+    }
+
+    /// When the checkout was last used: the newest of its index, HEAD and
+    /// reflog. Fetches don't count.
+    pub async fn last_activity(&self) -> Option<SystemTime> {
+        let mut newest = None;
+        for git_path in ["index", "HEAD", "logs/HEAD"] {
+            newest = newest.max(self.modified(git_path).await);
+        }
+        newest
+    }
+
+    async fn modified(&self, git_path: &str) -> Option<SystemTime> {
+        let path = self.git(&["rev-parse", "--git-path", git_path]).await.ok()?;
+        let path = self.path.join(path.trim());
+
+        tokio::fs::metadata(path).await.ok()?.modified().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn parses_porcelain_v2_status() {
+        let output = "\
+# branch.oid 1f0e6c1
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -3
+# stash 1
+1 .M N... 100644 100644 100644 3f2a 3f2a src/main.rs
+? notes.txt
+";
+
+        assert_eq!(
+            RepoStatus::parse(output),
+            RepoStatus {
+                branch: Some("main".into()),
+                upstream: Some("origin/main".into()),
+                ahead: 2,
+                behind: 3,
+                changes: 2,
+                stashes: 1,
+            }
+        );
+
+        let detached = RepoStatus::parse("# branch.oid 1f0e6c1\n# branch.head (detached)\n");
+        assert_eq!(detached.branch, None);
+        assert!(!detached.is_dirty());
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=gitnow", "-c", "user.email=gitnow@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn fetch_bare_updates_branches_without_worktrees() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("gitnow-fetch-{}", uuid::Uuid::new_v4()));
+        let origin = root.join("origin");
+        let bare = root.join("clone/.bare");
+        std::fs::create_dir_all(&origin)?;
+        git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+        git(&origin, &["commit", "--quiet", "--allow-empty", "-m", "initial"]);
+        git(&origin, &["branch", "idle"]);
+        git(&root, &["clone", "--quiet", "--bare", "origin", "clone/.bare"]);
+        git(&bare, &["worktree", "add", "--quiet", "../main", "main"]);
+        let checked_out = git(&bare, &["rev-parse", "main"]);
+
+        git(&origin, &["commit", "--quiet", "--allow-empty", "-m", "upstream"]);
+        git(&origin, &["branch", "--force", "idle", "main"]);
+        git(&origin, &["branch", "new", "main"]);
+        let upstream = git(&origin, &["rev-parse", "main"]);
+
+        GitRepo::new(bare.clone()).fetch_bare().await?;
+
+        assert_eq!(git(&bare, &["rev-parse", "idle"]), upstream);
+        assert_eq!(git(&bare, &["rev-parse", "new"]), upstream);
+        // The worktree's branch moves with `fast_forward` in the worktree.
+        assert_eq!(git(&bare, &["rev-parse", "main"]), checked_out);
+        assert_eq!(git(&bare, &["rev-parse", "origin/main"]), upstream);
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
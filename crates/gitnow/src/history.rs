@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{app::App, cache::CacheConfig, git_provider::Repository, layout::PathLayoutApp};
+
+pub const HISTORY_FILENAME: &str = "history.json";
+
+/// Oldest entries are dropped once the history grows beyond this size.
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// Frecency weight used when `settings.search.frecency_weight` is unset.
+pub const DEFAULT_FRECENCY_WEIGHT: f64 = 1.0;
+
+/// Converts a frecency value into fuzzy-match points. A single matched
+/// character is worth roughly 16 points in nucleo.
+const FRECENCY_SCALE: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryKind {
+    #[default]
+    Repository,
+    Worktree,
+    Project,
+}
+
+impl std::fmt::Display for HistoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryKind::Repository => write!(f, "repository"),
+            HistoryKind::Worktree => write!(f, "worktree"),
+            HistoryKind::Project => write!(f, "project"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    #[serde(default)]
+    pub kind: HistoryKind,
+    /// Relative repository path, e.g. `github.com/kjuulh/gitnow`, for
+    /// repositories and worktrees; the project name for projects.
+    #[serde(alias = "repository")]
+    pub name: String,
+    /// The directory that was opened. Empty in entries written before it
+    /// was recorded, until [`parse_entries`] fills it in.
+    #[serde(default)]
+    pub path: PathBuf,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Records opened repositories, worktrees and projects under the cache
+/// directory, and derives a frecency ranking from them.
+pub struct History {
+    app: &'static App,
+}
+
+impl History {
+    pub fn new(app: &'static App) -> Self {
+        Self { app }
+    }
+
+    fn location(&self) -> anyhow::Result<PathBuf> {
+        Ok(self.app.config.get_cache_location()?.join(HISTORY_FILENAME))
+    }
+
+    /// Returns every recorded entry, oldest first. A missing or unreadable
+    /// history is treated as empty.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let Ok(location) = self.location() else {
+            return Vec::new();
+        };
+        let Ok(content) = std::fs::read_to_string(&location) else {
+            return Vec::new();
+        };
+
+        parse_entries(&content, self.app.layout().directory())
+            .inspect_err(|e| tracing::warn!("failed to parse history, ignoring it: {}", e))
+            .unwrap_or_default()
+    }
+
+    /// Returns the most recent entry for each opened directory, newest first.
+    pub fn recent(&self) -> Vec<HistoryEntry> {
+        let mut seen = HashSet::new();
+        self.entries()
+            .into_iter()
+            .rev()
+            .filter(|entry| seen.insert(entry.path.clone()))
+            .collect()
+    }
+
+    pub fn record(&self, kind: HistoryKind, name: String, path: &Path) -> anyhow::Result<()> {
+        let mut entries = self.entries();
+        entries.push(HistoryEntry {
+            kind,
+            name,
+            path: path.to_path_buf(),
+            timestamp: Utc::now(),
+        });
+
+        if entries.len() > MAX_HISTORY_ENTRIES {
+            entries.drain(..entries.len() - MAX_HISTORY_ENTRIES);
+        }
+
+        let location = self.location()?;
+        if let Some(parent) = location.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(location, serde_json::to_string(&entries)?)?;
+
+        Ok(())
+    }
+
+    /// Like [`History::record`], but only logs failures; history must never
+    /// prevent the user from reaching a directory.
+    pub fn try_record(&self, kind: HistoryKind, name: String, path: &Path) {
+        if let Err(e) = self.record(kind, name, path) {
+            tracing::warn!("failed to record history: {}", e);
+        }
+    }
+
+    pub fn try_record_repository(&self, repository: &Repository, path: &Path) {
+        self.try_record(
+            HistoryKind::Repository,
+            repository.to_rel_path().display().to_string(),
+            path,
+        );
+    }
+
+    pub fn frecency(&self) -> Frecency {
+        let weight = self
+            .app
+            .config
+            .settings
+            .search
+            .as_ref()
+            .and_then(|s| s.frecency_weight)
+            .unwrap_or(DEFAULT_FRECENCY_WEIGHT);
+
+        if weight == 0.0 {
+            return Frecency::default();
+        }
+
+        Frecency::from_entries(&self.entries(), Utc::now(), weight)
+    }
+}
+
+/// Frecency bonuses keyed by relative repository path.
+#[derive(Debug, Default, Clone)]
+pub struct Frecency {
+    bonuses: HashMap<String, f64>,
+}
+
+impl Frecency {
+    pub fn from_entries(entries: &[HistoryEntry], now: DateTime<Utc>, weight: f64) -> Self {
+        let mut frecency: HashMap<String, f64> = HashMap::new();
+        for entry in entries.iter().filter(|e| e.kind != HistoryKind::Project) {
+            *frecency.entry(entry.name.clone()).or_default() +=
+                recency_weight(now.signed_duration_since(entry.timestamp));
+        }
+
+        Self {
+            bonuses: frecency
+                .into_iter()
+                .map(|(key, value)| (key, weight * FRECENCY_SCALE * value.ln_1p()))
+                .collect(),
+        }
+    }
+
+    /// Match points to add for `key`.
+    pub fn bonus(&self, key: &str) -> u32 {
+        self.bonuses
+            .get(key)
+            .map(|bonus| bonus.max(0.0).round() as u32)
+            .unwrap_or_default()
+    }
+}
+
+/// Visits within the last hour count the most, decaying to a quarter after
+/// a week.
+fn recency_weight(age: chrono::Duration) -> f64 {
+    if age < chrono::Duration::hours(1) {
+        4.0
+    } else if age < chrono::Duration::days(1) {
+        2.0
+    } else if age < chrono::Duration::weeks(1) {
+        0.5
+    } else {
+        0.25
+    }
+}
+
+pub trait HistoryApp {
+    fn history(&self) -> History;
+}
+
+impl HistoryApp for &'static App {
+    fn history(&self) -> History {
+        History::new(self)
+    }
+}
+
+/// Parses the history file. Entries from before paths were recorded only
+/// name a repository, which was then always cloned at `<directory>/<name>`.
+fn parse_entries(content: &str, directory: &Path) -> serde_json::Result<Vec<HistoryEntry>> {
+    let mut entries: Vec<HistoryEntry> = serde_json::from_str(content)?;
+    for entry in &mut entries {
+        if entry.path.as_os_str().is_empty() {
+            entry.path = directory.join(&entry.name);
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(repository: &str, timestamp: DateTime<Utc>) -> HistoryEntry {
+        HistoryEntry {
+            kind: HistoryKind::Repository,
+            name: repository.into(),
+            path: PathBuf::from("/git").join(repository),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn recent_and_frequent_repositories_get_larger_bonuses() {
+        let now: DateTime<Utc> = "2026-10-01T12:00:00Z".parse().unwrap();
+        let entries = vec![
+            entry("github.com/org/daily", now - chrono::Duration::minutes(5)),
+            entry("github.com/org/daily", now - chrono::Duration::minutes(30)),
+            entry("github.com/org/daily", now - chrono::Duration::hours(3)),
+            entry("github.com/org/once", now - chrono::Duration::minutes(10)),
+            entry("github.com/org/old", now - chrono::Duration::weeks(10)),
+        ];
+
+        let frecency = Frecency::from_entries(&entries, now, 1.0);
+
+        assert!(frecency.bonus("github.com/org/daily") > frecency.bonus("github.com/org/once"));
+        assert!(frecency.bonus("github.com/org/once") > frecency.bonus("github.com/org/old"));
+        assert_eq!(frecency.bonus("github.com/org/never"), 0);
+    }
+
+    #[test]
+    fn reads_entries_written_before_kinds_and_paths() {
+        let content = r#"[{"repository":"github.com/org/repo","timestamp":"2026-10-01T12:00:00Z"}]"#;
+
+        let entries = parse_entries(content, Path::new("/git")).unwrap();
+
+        assert_eq!(
+            entries,
+            [entry("github.com/org/repo", "2026-10-01T12:00:00Z".parse().unwrap())]
+        );
+    }
+
+    #[test]
+    fn weight_scales_bonuses() {
+        let now: DateTime<Utc> = "2026-10-01T12:00:00Z".parse().unwrap();
+        let entries = vec![entry("github.com/org/repo", now)];
+
+        let single = Frecency::from_entries(&entries, now, 1.0);
+        let double = Frecency::from_entries(&entries, now, 2.0);
+
+        assert!(single.bonus("github.com/org/repo") > 0);
+        assert_eq!(
+            double.bonus("github.com/org/repo"),
+            (2.0 * FRECENCY_SCALE * 4.0_f64.ln_1p()).round() as u32
+        );
+    }
+}
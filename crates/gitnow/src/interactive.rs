@@ -1,16 +1,90 @@
+use std::collections::HashMap;
+
 use app::App;
-use ratatui::{prelude::*, Terminal};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{ListDirection, ListState},
+    Terminal, TerminalOptions, Viewport,
+};
+
+use crate::{
+    fuzzy_matcher::FuzzyMatcher,
+    git_provider::Repository,
+    keymap::PickerAction,
+    query::{Qualifier, QueryContext},
+    theme::{Theme, ThemeApp},
+};
 
-use crate::git_provider::Repository;
+mod input;
+mod preview;
 
-pub trait Searchable: Clone {
+pub trait Searchable: Clone + Send + 'static {
     fn display_label(&self) -> String;
+
+    /// Whether the item satisfies a query qualifier such as `owner:foo`.
+    /// Items without fields ignore qualifiers, so typing one doesn't empty
+    /// their picker.
+    fn matches_qualifier(&self, _qualifier: &Qualifier, _context: &QueryContext) -> bool {
+        true
+    }
+
+    /// Whether the item exists locally. Items returning `None` get no marker.
+    fn local_state(&self, _context: &QueryContext) -> Option<LocalState> {
+        None
+    }
+
+    /// Details shown in the picker's preview pane. Called off the UI thread,
+    /// so it may be slow.
+    fn preview(&self, _context: &QueryContext) -> Option<Text<'static>> {
+        None
+    }
+}
+
+/// Where a repository lives relative to `projects.directory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalState {
+    Remote,
+    Cloned,
+    /// A bare clone with worktrees, see `gitnow worktree`.
+    Worktree,
+}
+
+impl LocalState {
+    fn marker(self) -> Span<'static> {
+        match self {
+            LocalState::Remote => Span::from("○ ").dark_gray(),
+            LocalState::Cloned => Span::from("● ").green(),
+            LocalState::Worktree => Span::from("◆ ").cyan(),
+        }
+    }
 }
 
 impl Searchable for Repository {
     fn display_label(&self) -> String {
         self.to_rel_path().display().to_string()
     }
+
+    fn matches_qualifier(&self, qualifier: &Qualifier, context: &QueryContext) -> bool {
+        qualifier.matches_repository(self, context)
+    }
+
+    fn local_state(&self, context: &QueryContext) -> Option<LocalState> {
+        let path = context.layout.path(self);
+        let state = if path.join(".bare").exists() {
+            LocalState::Worktree
+        } else if path.exists() {
+            LocalState::Cloned
+        } else {
+            LocalState::Remote
+        };
+
+        Some(state)
+    }
+
+    fn preview(&self, context: &QueryContext) -> Option<Text<'static>> {
+        Some(preview::repository_preview(self, context))
+    }
 }
 
 #[derive(Clone)]
@@ -22,6 +96,273 @@ impl Searchable for StringItem {
     }
 }
 
+/// A matched item along with what the pickers need to render it.
+struct MatchedItem<T> {
+    item: T,
+    /// Char indices of the label that matched the search.
+    indices: Vec<u32>,
+    state: Option<LocalState>,
+}
+
+impl<T: Searchable> MatchedItem<T> {
+    fn spans(&self, theme: &Theme) -> Vec<Span<'static>> {
+        let mut spans: Vec<Span> = self.state.map(LocalState::marker).into_iter().collect();
+        spans.extend(highlight(
+            &self.item.display_label(),
+            &self.indices,
+            theme.matched,
+        ));
+        spans
+    }
+}
+
+/// Splits `label` into spans, styling the chars at `indices` with `style`.
+fn highlight(label: &str, indices: &[u32], style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_matched = false;
+
+    for (i, c) in label.chars().enumerate() {
+        let matched = indices.binary_search(&(i as u32)).is_ok();
+        if matched != current_matched && !current.is_empty() {
+            spans.push(highlight_span(
+                std::mem::take(&mut current),
+                current_matched.then_some(style),
+            ));
+        }
+        current_matched = matched;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        spans.push(highlight_span(current, current_matched.then_some(style)));
+    }
+
+    spans
+}
+
+fn highlight_span(text: String, style: Option<Style>) -> Span<'static> {
+    match style {
+        Some(style) => Span::from(text).style(style),
+        None => Span::from(text),
+    }
+}
+
+/// Matches `items` against `search`. Local states hit the filesystem, so
+/// they're looked up once per label and cached in `states`.
+fn match_items<T: Searchable>(
+    matcher: &FuzzyMatcher,
+    search: &str,
+    items: &[T],
+    states: &mut HashMap<String, Option<LocalState>>,
+) -> Vec<MatchedItem<T>> {
+    matcher
+        .match_items_highlighted(search, items)
+        .into_iter()
+        .map(|(item, _, indices)| {
+            let state = *states
+                .entry(item.display_label())
+                .or_insert_with(|| item.local_state(matcher.context()));
+
+            MatchedItem {
+                item: item.clone(),
+                indices,
+                state,
+            }
+        })
+        .collect()
+}
+
+/// Applies a search editing action. Returns whether the search text changed.
+fn apply_edit(input: &mut input::SearchInput, action: PickerAction) -> bool {
+    match action {
+        PickerAction::CursorLeft => input.move_left(),
+        PickerAction::CursorRight => input.move_right(),
+        PickerAction::CursorStart => input.move_start(),
+        PickerAction::CursorEnd => input.move_end(),
+        PickerAction::WordLeft => input.move_word_left(),
+        PickerAction::WordRight => input.move_word_right(),
+        PickerAction::DeleteBackward => return input.delete_backward(),
+        PickerAction::DeleteForward => return input.delete_forward(),
+        PickerAction::DeleteWord => return input.delete_word(),
+        PickerAction::DeleteToStart => return input.delete_to_start(),
+        PickerAction::DeleteToEnd => return input.delete_to_end(),
+        _ => {}
+    }
+
+    false
+}
+
+/// Applies a movement action to a list of `len` items. `Up` always moves up
+/// on screen, which selects a later item when the list is drawn bottom to top.
+fn move_selection(
+    list: &mut ListState,
+    len: usize,
+    page_size: usize,
+    direction: ListDirection,
+    action: PickerAction,
+) {
+    let mut delta = match action {
+        PickerAction::Up => 1,
+        PickerAction::Down => -1,
+        PickerAction::PageUp => page_size as isize,
+        PickerAction::PageDown => -(page_size as isize),
+        _ => return,
+    };
+    if direction == ListDirection::TopToBottom {
+        delta = -delta;
+    }
+    if len == 0 {
+        return;
+    }
+
+    let current = list.selected().unwrap_or_default().min(len - 1);
+    list.select(Some(
+        current.saturating_add_signed(delta).min(len - 1),
+    ));
+}
+
+/// Ctrl-C, which always cancels, however the keys are bound.
+fn is_interrupt(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// The character to insert for an unbound key press, if any.
+fn typed_char(key: &KeyEvent) -> Option<char> {
+    match key.code {
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            Some(c)
+        }
+        _ => None,
+    }
+}
+
+/// Splits `area` into the list and the prompt line, placing the prompt
+/// where the list starts.
+fn list_and_input(area: Rect, direction: ListDirection) -> (Rect, Rect) {
+    match direction {
+        ListDirection::BottomToTop => {
+            let [list, input] =
+                Layout::vertical([Constraint::Percentage(100), Constraint::Min(1)]).areas(area);
+            (list, input)
+        }
+        ListDirection::TopToBottom => {
+            let [input, list] =
+                Layout::vertical([Constraint::Min(1), Constraint::Percentage(100)]).areas(area);
+            (list, input)
+        }
+    }
+}
+
+/// Takes a status row from the list area, on the side next to the prompt.
+fn split_status(list: Rect, direction: ListDirection) -> (Rect, Rect) {
+    match direction {
+        ListDirection::BottomToTop => {
+            let [list, status] =
+                Layout::vertical([Constraint::Percentage(100), Constraint::Min(1)]).areas(list);
+            (list, status)
+        }
+        ListDirection::TopToBottom => {
+            let [status, list] =
+                Layout::vertical([Constraint::Min(1), Constraint::Percentage(100)]).areas(list);
+            (list, status)
+        }
+    }
+}
+
+/// Renders the theme's border and title, if any, returning the area inside.
+fn render_frame_block(frame: &mut Frame, theme: &Theme) -> Rect {
+    let area = frame.area();
+    match theme.block() {
+        Some(block) => {
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+            inner
+        }
+        None => area,
+    }
+}
+
+/// A picker that can draw on either terminal backend.
+trait Picker {
+    type Output;
+
+    fn run<B: Backend>(self, terminal: &mut Terminal<B>) -> anyhow::Result<Self::Output>;
+}
+
+/// Runs `picker` on the whole screen, or inline below the cursor when
+/// `settings.ui.height` is set.
+fn run_picker<P: Picker>(theme: &Theme, picker: P) -> anyhow::Result<P::Output> {
+    let Some(height) = theme.height else {
+        let backend = TermwizBackend::new().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        let mut terminal = Terminal::new(backend)?;
+        let output = picker.run(&mut terminal);
+        terminal.resize(Rect::ZERO)?;
+        return output;
+    };
+
+    let (_, rows) = crossterm::terminal::size()?;
+    crossterm::terminal::enable_raw_mode()?;
+    let _guard = RawModeGuard;
+
+    let mut terminal = Terminal::with_options(
+        CrosstermBackend::new(std::io::stderr()),
+        TerminalOptions {
+            viewport: Viewport::Inline(height.lines(rows)),
+        },
+    )?;
+    let output = picker.run(&mut terminal);
+    terminal.clear()?;
+
+    output
+}
+
+struct RawModeGuard;
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+/// Items streamed into an open picker, labelled by where they came from.
+pub struct ItemBatch<T> {
+    pub source: String,
+    pub items: anyhow::Result<Vec<T>>,
+}
+
+/// Loads items in the background of an open picker, e.g. repositories from
+/// each provider. The picker shows a spinner until every batch has arrived,
+/// and Ctrl-R reloads from scratch.
+pub struct ItemLoader<T> {
+    load: Box<dyn Fn() -> tokio::sync::mpsc::UnboundedReceiver<ItemBatch<T>>>,
+    load_on_open: bool,
+}
+
+impl<T> ItemLoader<T> {
+    pub fn new(
+        load: impl Fn() -> tokio::sync::mpsc::UnboundedReceiver<ItemBatch<T>> + 'static,
+    ) -> Self {
+        Self {
+            load: Box::new(load),
+            load_on_open: false,
+        }
+    }
+
+    /// Starts loading as soon as the picker opens, instead of only on refresh.
+    pub fn load_on_open(mut self) -> Self {
+        self.load_on_open = true;
+        self
+    }
+
+    fn load(&self) -> tokio::sync::mpsc::UnboundedReceiver<ItemBatch<T>> {
+        (self.load)()
+    }
+}
+
 pub struct Interactive {
     app: &'static crate::app::App,
 }
@@ -42,20 +383,49 @@ impl Interactive {
         &mut self,
         items: &[T],
     ) -> anyhow::Result<Option<T>> {
-        let backend = TermwizBackend::new().map_err(|e| anyhow::anyhow!(e.to_string()))?;
-        let terminal = Terminal::new(backend)?;
+        Ok(self
+            .interactive_search_with_actions(items, Vec::new())?
+            .map(|(item, _)| item))
+    }
 
-        App::new(self.app, items).run(terminal)
+    /// Like [`Interactive::interactive_search_items`], but Tab or Ctrl-O opens
+    /// a menu of `actions` for the selected item. Returns the index of the
+    /// chosen action, or `None` when the item was picked with Enter.
+    pub fn interactive_search_with_actions<T: Searchable>(
+        &mut self,
+        items: &[T],
+        actions: Vec<String>,
+    ) -> anyhow::Result<Option<(T, Option<usize>)>> {
+        run_picker(
+            &self.app.theme(),
+            App::new(self.app, items.to_vec()).with_actions(actions),
+        )
+    }
+
+    /// Like [`Interactive::interactive_search_with_actions`], but opens right
+    /// away and streams in items from `loader` while the user types.
+    pub fn interactive_search_with_loader<T: Searchable>(
+        &mut self,
+        items: Vec<T>,
+        loader: ItemLoader<T>,
+        actions: Vec<String>,
+    ) -> anyhow::Result<Option<(T, Option<usize>)>> {
+        run_picker(
+            &self.app.theme(),
+            App::new(self.app, items)
+                .with_actions(actions)
+                .with_loader(loader),
+        )
     }
 
     pub fn interactive_multi_search<T: Searchable>(
         &mut self,
         items: &[T],
     ) -> anyhow::Result<Vec<T>> {
-        let backend = TermwizBackend::new().map_err(|e| anyhow::anyhow!(e.to_string()))?;
-        let terminal = Terminal::new(backend)?;
-
-        multi_select::MultiSelectApp::new(self.app, items).run(terminal)
+        run_picker(
+            &self.app.theme(),
+            multi_select::MultiSelectApp::new(self.app, items),
+        )
     }
 }
 
@@ -70,201 +440,562 @@ impl InteractiveApp for &'static crate::app::App {
 }
 
 mod app {
-    use crossterm::event::KeyModifiers;
+    use std::{
+        collections::{HashMap, HashSet},
+        time::Duration,
+    };
+
     use ratatui::{
-        crossterm::event::{self, Event, KeyCode},
-        layout::{Constraint, Layout},
-        prelude::TermwizBackend,
-        style::{Style, Stylize},
-        text::{Line, Span},
-        widgets::{ListItem, ListState, Paragraph, StatefulWidget},
+        crossterm::event::{self, Event},
+        layout::{Constraint, Layout, Rect},
+        prelude::Backend,
+        style::Stylize,
+        text::{Line, Span, Text},
+        widgets::{Block, Clear, ListDirection, ListItem, ListState, Paragraph, StatefulWidget, Wrap},
         Frame, Terminal,
     };
 
-    use crate::fuzzy_matcher::FuzzyMatcherApp;
+    use tokio::sync::mpsc::{self, error::TryRecvError};
 
-    use super::Searchable;
+    use crate::{
+        components::{
+            spinner::{Spinner, SpinnerState},
+            Msg,
+        },
+        fuzzy_matcher::{FuzzyMatcher, FuzzyMatcherApp},
+        keymap::{Keymap, KeymapApp, PickerAction},
+        theme::{Theme, ThemeApp},
+    };
 
-    pub struct App<'a, T: Searchable> {
-        app: &'static crate::app::App,
-        items: &'a [T],
-        current_search: String,
-        matched_items: Vec<T>,
+    use super::{
+        apply_edit, input::SearchInput, list_and_input, match_items, move_selection,
+        is_interrupt, preview::Previewer, render_frame_block, split_status, typed_char, ItemBatch,
+        ItemLoader, LocalState, MatchedItem, Picker, Searchable,
+    };
+
+    /// How often the event loop wakes up to pick up finished previews and
+    /// loaded items.
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    const ACTIONS: &[PickerAction] = &[
+        PickerAction::Accept,
+        PickerAction::Cancel,
+        PickerAction::Up,
+        PickerAction::Down,
+        PickerAction::PageUp,
+        PickerAction::PageDown,
+        PickerAction::CursorLeft,
+        PickerAction::CursorRight,
+        PickerAction::CursorStart,
+        PickerAction::CursorEnd,
+        PickerAction::WordLeft,
+        PickerAction::WordRight,
+        PickerAction::DeleteBackward,
+        PickerAction::DeleteForward,
+        PickerAction::DeleteWord,
+        PickerAction::DeleteToStart,
+        PickerAction::DeleteToEnd,
+        PickerAction::TogglePreview,
+        PickerAction::Actions,
+        PickerAction::Refresh,
+    ];
+
+    const MENU_ACTIONS: &[PickerAction] = &[
+        PickerAction::Accept,
+        PickerAction::Cancel,
+        PickerAction::Actions,
+        PickerAction::Up,
+        PickerAction::Down,
+    ];
+
+    /// Below this width the preview is shown above the list instead of beside it.
+    const SIDE_PREVIEW_MIN_WIDTH: u16 = 100;
+
+    pub struct App<T: Searchable> {
+        matcher: FuzzyMatcher,
+        items: Vec<T>,
+        input: SearchInput,
+        matched_items: Vec<MatchedItem<T>>,
+        local_states: HashMap<String, Option<LocalState>>,
         list: ListState,
+        previewer: Option<Previewer<T>>,
+        show_preview: bool,
+        /// Finished previews keyed by label. `None` means the item has none.
+        previews: HashMap<String, Option<Text<'static>>>,
+        requested_preview: Option<String>,
+        actions: Vec<String>,
+        /// Set while the action menu is open.
+        action_menu: Option<ListState>,
+        keymap: Keymap,
+        theme: Theme,
+        /// Rows in the list at the last draw, for paging.
+        page_size: usize,
+        loader: Option<ItemLoader<T>>,
+        /// Set while batches are still arriving.
+        loading: Option<mpsc::UnboundedReceiver<ItemBatch<T>>>,
+        /// Labels of `items`, so items listed by several sources show once.
+        labels: HashSet<String>,
+        /// Sources that failed during the last load.
+        failures: Vec<String>,
+        spinner: SpinnerState,
     }
 
-    impl<'a, T: Searchable> App<'a, T> {
-        pub fn new(app: &'static crate::app::App, items: &'a [T]) -> Self {
+    impl<T: Searchable> App<T> {
+        pub fn new(app: &'static crate::app::App, items: Vec<T>) -> Self {
             Self {
-                app,
+                matcher: app.fuzzy_matcher(),
                 items,
-                current_search: String::default(),
+                input: SearchInput::default(),
                 matched_items: Vec::default(),
+                local_states: HashMap::new(),
                 list: ListState::default(),
+                previewer: None,
+                show_preview: false,
+                previews: HashMap::new(),
+                requested_preview: None,
+                actions: Vec::new(),
+                action_menu: None,
+                keymap: app.keymap(),
+                theme: app.theme(),
+                page_size: 1,
+                loader: None,
+                loading: None,
+                labels: HashSet::new(),
+                failures: Vec::new(),
+                spinner: SpinnerState::default(),
             }
         }
 
+        pub fn with_loader(mut self, loader: ItemLoader<T>) -> Self {
+            self.labels = self.items.iter().map(|i| i.display_label()).collect();
+            if loader.load_on_open {
+                self.loading = Some(loader.load());
+            }
+            self.loader = Some(loader);
+            self
+        }
+
+        /// Drops the current items and loads them again from the loader.
+        fn refresh(&mut self) {
+            let Some(loader) = &self.loader else {
+                return;
+            };
+
+            self.loading = Some(loader.load());
+            self.items.clear();
+            self.labels.clear();
+            self.failures.clear();
+            self.list.select(None);
+            self.update_matched_items();
+        }
+
+        /// Adds loaded items. Returns whether anything changed.
+        fn receive_items(&mut self) -> bool {
+            let Some(loading) = &mut self.loading else {
+                return false;
+            };
+
+            let mut received = false;
+            loop {
+                match loading.try_recv() {
+                    Ok(batch) => {
+                        match batch.items {
+                            Ok(items) => {
+                                for item in items {
+                                    if self.labels.insert(item.display_label()) {
+                                        self.items.push(item);
+                                    }
+                                }
+                            }
+                            Err(e) => self.failures.push(format!("{}: {e:#}", batch.source)),
+                        }
+                        received = true;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.loading = None;
+                        received = true;
+                        break;
+                    }
+                }
+            }
+
+            if received {
+                self.update_matched_items();
+            }
+            received
+        }
+
+        pub fn with_actions(mut self, actions: Vec<String>) -> Self {
+            self.actions = actions;
+            self
+        }
+
+        fn open_action_menu(&mut self) {
+            if !self.actions.is_empty() && self.selected_item().is_some() {
+                self.action_menu = Some(ListState::default().with_selected(Some(0)));
+            }
+        }
+
+        fn selected_item(&self) -> Option<&T> {
+            self.list
+                .selected()
+                .and_then(|i| self.matched_items.get(i))
+                .map(|m| &m.item)
+        }
+
+        fn toggle_preview(&mut self) {
+            self.show_preview = !self.show_preview;
+            if self.show_preview && self.previewer.is_none() {
+                self.previewer = Some(Previewer::new(self.matcher.context().clone()));
+            }
+        }
+
+        /// Asks for the selected item's preview unless it's known or pending.
+        fn request_preview(&mut self) {
+            let Some(previewer) = self.previewer.as_ref().filter(|_| self.show_preview) else {
+                return;
+            };
+            let Some(matched) = self.list.selected().and_then(|i| self.matched_items.get(i))
+            else {
+                return;
+            };
+
+            let label = matched.item.display_label();
+            if self.previews.contains_key(&label)
+                || self.requested_preview.as_ref() == Some(&label)
+            {
+                return;
+            }
+
+            previewer.request(matched.item.clone());
+            self.requested_preview = Some(label);
+        }
+
+        /// Stores finished previews. Returns whether any arrived.
+        fn receive_previews(&mut self) -> bool {
+            let Some(previewer) = &self.previewer else {
+                return false;
+            };
+
+            let mut received = false;
+            while let Some((label, preview)) = previewer.try_recv() {
+                if self.requested_preview.as_ref() == Some(&label) {
+                    self.requested_preview = None;
+                }
+                self.previews.insert(label, preview);
+                received = true;
+            }
+
+            received
+        }
+
         fn update_matched_items(&mut self) {
-            let labels: Vec<String> = self.items.iter().map(|i| i.display_label()).collect();
-            let label_refs: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
-
-            let matched_keys = self
-                .app
-                .fuzzy_matcher()
-                .match_pattern(&self.current_search, &label_refs);
-
-            self.matched_items = matched_keys
-                .into_iter()
-                .filter_map(|key| {
-                    self.items
-                        .iter()
-                        .find(|i| i.display_label() == key)
-                        .cloned()
-                })
-                .collect();
+            self.matched_items = match_items(
+                &self.matcher,
+                &self.input.text(),
+                &self.items,
+                &mut self.local_states,
+            );
 
             if self.list.selected().is_none() {
                 self.list.select_first();
             }
         }
 
-        pub fn run(
-            mut self,
-            mut terminal: Terminal<TermwizBackend>,
-        ) -> anyhow::Result<Option<T>> {
+    }
+
+    impl<T: Searchable> Picker for App<T> {
+        type Output = Option<(T, Option<usize>)>;
+
+        fn run<B: Backend>(mut self, terminal: &mut Terminal<B>) -> anyhow::Result<Self::Output> {
             self.update_matched_items();
+            let mut redraw = true;
 
             loop {
-                terminal.draw(|frame| self.draw(frame))?;
+                // Also on first open and once items load, not only after keys.
+                self.request_preview();
+                if redraw {
+                    terminal.draw(|frame| self.draw(frame))?;
+                }
+                redraw = self.receive_previews() | self.receive_items();
+                if self.loading.is_some() {
+                    self.spinner.update(&Msg::Tick);
+                    redraw = true;
+                }
 
-                if let Event::Key(key) = event::read()? {
-                    if let KeyCode::Char('c') = key.code
-                        && key.modifiers.contains(KeyModifiers::CONTROL)
-                    {
-                        return Ok(None);
-                    }
+                if !event::poll(POLL_INTERVAL)? {
+                    continue;
+                }
+                redraw = true;
 
-                    match key.code {
-                        KeyCode::Char(letter) => {
-                            self.current_search.push(letter);
-                            self.update_matched_items();
+                let Event::Key(key) = event::read()? else {
+                    continue;
+                };
+                if is_interrupt(&key) {
+                    return Ok(None);
+                }
+
+                if let Some(menu) = &mut self.action_menu {
+                    match self.keymap.resolve(&key, MENU_ACTIONS) {
+                        Some(PickerAction::Cancel | PickerAction::Actions) => {
+                            self.action_menu = None
                         }
-                        KeyCode::Backspace => {
-                            if !self.current_search.is_empty() {
-                                let _ =
-                                    self.current_search.remove(self.current_search.len() - 1);
-                                self.update_matched_items();
+                        Some(PickerAction::Up) => menu.select_previous(),
+                        Some(PickerAction::Down) => menu.select_next(),
+                        Some(PickerAction::Accept) => {
+                            if let Some(action) = menu.selected()
+                                && let Some(item) = self.selected_item().cloned()
+                            {
+                                return Ok(Some((item, Some(action))));
                             }
                         }
-                        KeyCode::Esc => {
-                            return Ok(None);
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                match self.keymap.resolve(&key, ACTIONS) {
+                    Some(PickerAction::Cancel) => return Ok(None),
+                    Some(PickerAction::Accept) => {
+                        if let Some(item) = self.selected_item().cloned() {
+                            return Ok(Some((item, None)));
                         }
-                        KeyCode::Enter => {
-                            if let Some(selected) = self.list.selected()
-                                && let Some(item) =
-                                    self.matched_items.get(selected).cloned()
-                            {
-                                terminal.resize(ratatui::layout::Rect::ZERO)?;
-                                return Ok(Some(item));
-                            }
 
-                            return Ok(None);
+                        return Ok(None);
+                    }
+                    Some(PickerAction::TogglePreview) => self.toggle_preview(),
+                    Some(PickerAction::Actions) => self.open_action_menu(),
+                    Some(PickerAction::Refresh) => self.refresh(),
+                    Some(action) => {
+                        move_selection(
+                            &mut self.list,
+                            self.matched_items.len(),
+                            self.page_size,
+                            self.theme.direction,
+                            action,
+                        );
+                        if apply_edit(&mut self.input, action) {
+                            self.update_matched_items();
+                        }
+                    }
+                    None => {
+                        if let Some(c) = typed_char(&key) {
+                            self.input.insert(c);
+                            self.update_matched_items();
                         }
-                        KeyCode::Up => self.list.select_next(),
-                        KeyCode::Down => self.list.select_previous(),
-                        _ => {}
                     }
                 }
             }
         }
+    }
 
+    impl<T: Searchable> App<T> {
         fn draw(&mut self, frame: &mut Frame) {
-            let vertical = Layout::vertical([Constraint::Percentage(100), Constraint::Min(1)]);
-            let [list_area, input_area] = vertical.areas(frame.area());
+            let mut area = render_frame_block(frame, &self.theme);
+            if self.show_preview {
+                let preview_area;
+                if area.width >= SIDE_PREVIEW_MIN_WIDTH {
+                    [area, preview_area] =
+                        Layout::horizontal([Constraint::Percentage(50); 2]).areas(area);
+                } else if self.theme.direction == ListDirection::TopToBottom {
+                    [area, preview_area] =
+                        Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
+                            .areas(area);
+                } else {
+                    [preview_area, area] =
+                        Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)])
+                            .areas(area);
+                }
+                self.draw_preview(frame, preview_area);
+            }
 
-            let display_items: Vec<String> =
-                self.matched_items.iter().map(|i| i.display_label()).collect();
+            let (mut list_area, input_area) = list_and_input(area, self.theme.direction);
+            if self.loading.is_some() || !self.failures.is_empty() {
+                let status_area;
+                (list_area, status_area) = split_status(list_area, self.theme.direction);
+                self.draw_status(frame, status_area);
+            }
+            self.page_size = list_area.height.max(1) as usize;
 
-            let list_items: Vec<ListItem> =
-                display_items.into_iter().map(ListItem::from).collect();
+            let list_items: Vec<ListItem> = self
+                .matched_items
+                .iter()
+                .map(|m| ListItem::from(Line::from(m.spans(&self.theme))))
+                .collect();
 
             let list = ratatui::widgets::List::new(list_items)
-                .direction(ratatui::widgets::ListDirection::BottomToTop)
+                .direction(self.theme.direction)
                 .scroll_padding(3)
-                .highlight_symbol("> ")
+                .highlight_symbol(&self.theme.highlight_symbol)
                 .highlight_spacing(ratatui::widgets::HighlightSpacing::Always)
-                .highlight_style(Style::default().bold().white());
+                .highlight_style(self.theme.highlight);
 
             StatefulWidget::render(list, list_area, frame.buffer_mut(), &mut self.list);
 
-            let input = Paragraph::new(Line::from(vec![
-                Span::from("> ").blue(),
-                Span::from(self.current_search.as_str()),
-                Span::from(" ").on_white(),
-            ]));
+            let input =
+                Paragraph::new(self.input.line(Span::from("> ").style(self.theme.prompt)));
 
             frame.render_widget(input, input_area);
+
+            if let Some(menu) = &mut self.action_menu {
+                let width = self.actions.iter().map(|a| a.len()).max().unwrap_or_default() as u16;
+                let area = list_area.inner(ratatui::layout::Margin::new(2, 0));
+                let area = Rect {
+                    width: (width + 6).min(area.width),
+                    height: (self.actions.len() as u16 + 2).min(area.height),
+                    ..area
+                };
+                let area = match self.theme.direction {
+                    ListDirection::BottomToTop => Rect {
+                        y: list_area.bottom().saturating_sub(area.height),
+                        ..area
+                    },
+                    ListDirection::TopToBottom => area,
+                };
+
+                let menu_list = ratatui::widgets::List::new(
+                    self.actions.iter().map(|a| ListItem::from(a.as_str())),
+                )
+                .block(
+                    Block::bordered()
+                        .title(" actions ")
+                        .border_style(self.theme.border),
+                )
+                .highlight_symbol(&self.theme.highlight_symbol)
+                .highlight_spacing(ratatui::widgets::HighlightSpacing::Always)
+                .highlight_style(self.theme.highlight);
+
+                frame.render_widget(Clear, area);
+                StatefulWidget::render(menu_list, area, frame.buffer_mut(), menu);
+            }
+        }
+
+        /// A spinner while loading, then any sources that failed.
+        fn draw_status(&mut self, frame: &mut Frame, area: Rect) {
+            if self.loading.is_some() {
+                let spinner = Spinner::new(
+                    Span::from(format!("loading… {} items", self.items.len()))
+                        .style(self.theme.border),
+                )
+                .style(self.theme.spinner);
+                StatefulWidget::render(spinner, area, frame.buffer_mut(), &mut self.spinner);
+                return;
+            }
+
+            let failures =
+                Line::from(format!("failed to load {}", self.failures.join(", "))).red();
+            frame.render_widget(Paragraph::new(failures), area);
+        }
+
+        fn draw_preview(&self, frame: &mut Frame, area: Rect) {
+            let label = self
+                .list
+                .selected()
+                .and_then(|i| self.matched_items.get(i))
+                .map(|m| m.item.display_label());
+
+            let text = match label.and_then(|label| self.previews.get(&label)) {
+                Some(Some(preview)) => preview.clone(),
+                Some(None) => Text::from("no preview").dark_gray(),
+                None if self.matched_items.is_empty() => Text::default(),
+                None => Text::from("loading…").dark_gray(),
+            };
+
+            let preview = Paragraph::new(text)
+                .block(
+                    Block::bordered()
+                        .title(" preview ")
+                        .border_style(self.theme.border),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(preview, area);
         }
     }
 }
 
 pub mod multi_select {
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
 
-    use crossterm::event::KeyModifiers;
     use ratatui::{
-        crossterm::event::{self, Event, KeyCode},
+        crossterm::event::{self, Event},
         layout::{Constraint, Layout},
-        prelude::TermwizBackend,
-        style::{Style, Stylize},
+        prelude::Backend,
+        style::Stylize,
         text::{Line, Span},
         widgets::{ListItem, ListState, Paragraph, StatefulWidget},
         Frame, Terminal,
     };
 
-    use crate::fuzzy_matcher::FuzzyMatcherApp;
+    use crate::{
+        fuzzy_matcher::{FuzzyMatcher, FuzzyMatcherApp},
+        keymap::{Keymap, KeymapApp, PickerAction},
+        theme::{Theme, ThemeApp},
+    };
+
+    use super::{
+        apply_edit, input::SearchInput, list_and_input, match_items, move_selection,
+        is_interrupt, render_frame_block, typed_char, LocalState, MatchedItem, Picker, Searchable,
+    };
 
-    use super::Searchable;
+    const ACTIONS: &[PickerAction] = &[
+        PickerAction::Accept,
+        PickerAction::Cancel,
+        PickerAction::Up,
+        PickerAction::Down,
+        PickerAction::PageUp,
+        PickerAction::PageDown,
+        PickerAction::CursorLeft,
+        PickerAction::CursorRight,
+        PickerAction::CursorStart,
+        PickerAction::CursorEnd,
+        PickerAction::WordLeft,
+        PickerAction::WordRight,
+        PickerAction::DeleteBackward,
+        PickerAction::DeleteForward,
+        PickerAction::DeleteWord,
+        PickerAction::DeleteToStart,
+        PickerAction::DeleteToEnd,
+        PickerAction::ToggleSelection,
+    ];
 
     pub struct MultiSelectApp<'a, T: Searchable> {
-        app: &'static crate::app::App,
+        matcher: FuzzyMatcher,
         items: &'a [T],
-        current_search: String,
-        matched_items: Vec<T>,
+        input: SearchInput,
+        matched_items: Vec<MatchedItem<T>>,
+        local_states: HashMap<String, Option<LocalState>>,
         selected_labels: HashSet<String>,
         list: ListState,
+        keymap: Keymap,
+        theme: Theme,
+        /// Rows in the list at the last draw, for paging.
+        page_size: usize,
     }
 
     impl<'a, T: Searchable> MultiSelectApp<'a, T> {
         pub fn new(app: &'static crate::app::App, items: &'a [T]) -> Self {
             Self {
-                app,
+                matcher: app.fuzzy_matcher(),
                 items,
-                current_search: String::default(),
+                input: SearchInput::default(),
                 matched_items: Vec::default(),
+                local_states: HashMap::new(),
                 selected_labels: HashSet::new(),
                 list: ListState::default(),
+                keymap: app.keymap(),
+                theme: app.theme(),
+                page_size: 1,
             }
         }
 
         fn update_matched_items(&mut self) {
-            let labels: Vec<String> = self.items.iter().map(|i| i.display_label()).collect();
-            let label_refs: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
-
-            let matched_keys = self
-                .app
-                .fuzzy_matcher()
-                .match_pattern(&self.current_search, &label_refs);
-
-            self.matched_items = matched_keys
-                .into_iter()
-                .filter_map(|key| {
-                    self.items
-                        .iter()
-                        .find(|i| i.display_label() == key)
-                        .cloned()
-                })
-                .collect();
+            self.matched_items = match_items(
+                &self.matcher,
+                &self.input.text(),
+                self.items,
+                &mut self.local_states,
+            );
 
             if self.list.selected().is_none() {
                 self.list.select_first();
@@ -272,111 +1003,107 @@ pub mod multi_select {
         }
 
         fn toggle_current(&mut self) {
-            if let Some(selected) = self.list.selected() {
-                if let Some(item) = self.matched_items.get(selected) {
-                    let label = item.display_label();
-                    if !self.selected_labels.remove(&label) {
-                        self.selected_labels.insert(label);
-                    }
+            if let Some(selected) = self.list.selected()
+                && let Some(matched) = self.matched_items.get(selected)
+            {
+                let label = matched.item.display_label();
+                if !self.selected_labels.remove(&label) {
+                    self.selected_labels.insert(label);
                 }
             }
         }
 
-        pub fn run(
-            mut self,
-            mut terminal: Terminal<TermwizBackend>,
-        ) -> anyhow::Result<Vec<T>> {
+    }
+
+    impl<T: Searchable> Picker for MultiSelectApp<'_, T> {
+        type Output = Vec<T>;
+
+        fn run<B: Backend>(mut self, terminal: &mut Terminal<B>) -> anyhow::Result<Self::Output> {
             self.update_matched_items();
 
             loop {
                 terminal.draw(|frame| self.draw(frame))?;
 
-                if let Event::Key(key) = event::read()? {
-                    if let KeyCode::Char('c') = key.code
-                        && key.modifiers.contains(KeyModifiers::CONTROL)
-                    {
-                        return Ok(Vec::new());
-                    }
+                let Event::Key(key) = event::read()? else {
+                    continue;
+                };
+                if is_interrupt(&key) {
+                    return Ok(Vec::new());
+                }
 
-                    match key.code {
-                        KeyCode::Tab => {
-                            self.toggle_current();
+                match self.keymap.resolve(&key, ACTIONS) {
+                    Some(PickerAction::Cancel) => return Ok(Vec::new()),
+                    Some(PickerAction::ToggleSelection) => self.toggle_current(),
+                    Some(PickerAction::Accept) => {
+                        if self.selected_labels.is_empty() {
+                            // Don't allow confirming with no selections
+                            continue;
                         }
-                        KeyCode::Char(letter) => {
-                            self.current_search.push(letter);
+                        let selected: Vec<T> = self
+                            .items
+                            .iter()
+                            .filter(|i| self.selected_labels.contains(&i.display_label()))
+                            .cloned()
+                            .collect();
+                        return Ok(selected);
+                    }
+                    Some(action) => {
+                        move_selection(
+                            &mut self.list,
+                            self.matched_items.len(),
+                            self.page_size,
+                            self.theme.direction,
+                            action,
+                        );
+                        if apply_edit(&mut self.input, action) {
                             self.update_matched_items();
                         }
-                        KeyCode::Backspace => {
-                            if !self.current_search.is_empty() {
-                                let _ =
-                                    self.current_search.remove(self.current_search.len() - 1);
-                                self.update_matched_items();
-                            }
-                        }
-                        KeyCode::Esc => {
-                            return Ok(Vec::new());
-                        }
-                        KeyCode::Enter => {
-                            if self.selected_labels.is_empty() {
-                                // Don't allow confirming with no selections
-                                continue;
-                            }
-                            terminal.resize(ratatui::layout::Rect::ZERO)?;
-                            let selected: Vec<T> = self
-                                .items
-                                .iter()
-                                .filter(|i| self.selected_labels.contains(&i.display_label()))
-                                .cloned()
-                                .collect();
-                            return Ok(selected);
+                    }
+                    None => {
+                        if let Some(c) = typed_char(&key) {
+                            self.input.insert(c);
+                            self.update_matched_items();
                         }
-                        KeyCode::Up => self.list.select_next(),
-                        KeyCode::Down => self.list.select_previous(),
-                        _ => {}
                     }
                 }
             }
         }
+    }
 
+    impl<T: Searchable> MultiSelectApp<'_, T> {
         fn draw(&mut self, frame: &mut Frame) {
-            let vertical = Layout::vertical([
-                Constraint::Percentage(100),
-                Constraint::Min(1),
-                Constraint::Min(1),
-            ]);
-            let [list_area, input_area, hint_area] = vertical.areas(frame.area());
+            let area = render_frame_block(frame, &self.theme);
+            let [area, hint_area] =
+                Layout::vertical([Constraint::Percentage(100), Constraint::Min(1)]).areas(area);
+            let (list_area, input_area) = list_and_input(area, self.theme.direction);
+            self.page_size = list_area.height.max(1) as usize;
 
             let list_items: Vec<ListItem> = self
                 .matched_items
                 .iter()
-                .map(|i| {
-                    let label = i.display_label();
-                    let marker = if self.selected_labels.contains(&label) {
+                .map(|m| {
+                    let marker = if self.selected_labels.contains(&m.item.display_label()) {
                         "[x] "
                     } else {
                         "[ ] "
                     };
-                    ListItem::from(Line::from(vec![
-                        Span::from(marker).green(),
-                        Span::from(label),
-                    ]))
+                    let mut spans = vec![Span::from(marker).green()];
+                    spans.extend(m.spans(&self.theme));
+                    ListItem::from(Line::from(spans))
                 })
                 .collect();
 
             let list = ratatui::widgets::List::new(list_items)
-                .direction(ratatui::widgets::ListDirection::BottomToTop)
+                .direction(self.theme.direction)
                 .scroll_padding(3)
-                .highlight_symbol("> ")
+                .highlight_symbol(&self.theme.highlight_symbol)
                 .highlight_spacing(ratatui::widgets::HighlightSpacing::Always)
-                .highlight_style(Style::default().bold().white());
+                .highlight_style(self.theme.highlight);
 
             StatefulWidget::render(list, list_area, frame.buffer_mut(), &mut self.list);
 
-            let input = Paragraph::new(Line::from(vec![
-                Span::from("> ").blue(),
-                Span::from(self.current_search.as_str()),
-                Span::from(" ").on_white(),
-            ]));
+            let input =
+                Paragraph::new(self.input.line(Span::from("> ").style(self.theme.prompt)));
             frame.render_widget(input, input_area);
 
             let count = self.selected_labels.len();
@@ -388,3 +1115,34 @@ pub mod multi_select {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_groups_matched_chars() {
+        let spans = highlight("gitnow", &[0, 1, 4], Style::default().fg(Color::Yellow));
+
+        let parts: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style.fg == Some(Color::Yellow)))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![("gi", true), ("tn", false), ("o", true), ("w", false)]
+        );
+    }
+
+    #[test]
+    fn items_without_fields_ignore_qualifiers() {
+        let items = [StringItem("gitnow".into()), StringItem("cuddle".into())];
+
+        let matched: Vec<&str> = FuzzyMatcher::new()
+            .match_items("owner:kjuulh is:cloned gitn", &items)
+            .into_iter()
+            .map(|(item, _)| item.0.as_str())
+            .collect();
+        assert_eq!(matched, vec!["gitnow"]);
+    }
+}
@@ -0,0 +1,167 @@
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span},
+};
+
+/// The picker's search line, edited readline-style around a cursor.
+#[derive(Debug, Default, Clone)]
+pub struct SearchInput {
+    chars: Vec<char>,
+    /// Char index the next insert goes to, `0..=chars.len()`.
+    cursor: usize,
+}
+
+impl SearchInput {
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn move_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start();
+    }
+
+    pub fn move_word_right(&mut self) {
+        let len = self.chars.len();
+        let mut cursor = self.cursor;
+        while cursor < len && self.chars[cursor].is_whitespace() {
+            cursor += 1;
+        }
+        while cursor < len && !self.chars[cursor].is_whitespace() {
+            cursor += 1;
+        }
+        self.cursor = cursor;
+    }
+
+    /// Each edit returns whether the text changed.
+    pub fn delete_backward(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        self.chars.remove(self.cursor);
+        true
+    }
+
+    pub fn delete_forward(&mut self) -> bool {
+        if self.cursor == self.chars.len() {
+            return false;
+        }
+        self.chars.remove(self.cursor);
+        true
+    }
+
+    pub fn delete_word(&mut self) -> bool {
+        let start = self.word_start();
+        self.delete_range(start, self.cursor)
+    }
+
+    pub fn delete_to_start(&mut self) -> bool {
+        self.delete_range(0, self.cursor)
+    }
+
+    pub fn delete_to_end(&mut self) -> bool {
+        self.delete_range(self.cursor, self.chars.len())
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) -> bool {
+        if start == end {
+            return false;
+        }
+        self.chars.drain(start..end);
+        self.cursor = start;
+        true
+    }
+
+    /// Start of the word before the cursor, skipping trailing whitespace.
+    fn word_start(&self) -> usize {
+        let mut cursor = self.cursor;
+        while cursor > 0 && self.chars[cursor - 1].is_whitespace() {
+            cursor -= 1;
+        }
+        while cursor > 0 && !self.chars[cursor - 1].is_whitespace() {
+            cursor -= 1;
+        }
+        cursor
+    }
+
+    /// Renders the input behind `prompt`, with the cursor shown as an
+    /// inverted cell.
+    pub fn line(&self, prompt: Span<'static>) -> Line<'static> {
+        let before: String = self.chars[..self.cursor].iter().collect();
+        let under = self.chars.get(self.cursor).copied().unwrap_or(' ');
+        let after: String = self.chars.get(self.cursor + 1..).unwrap_or_default().iter().collect();
+
+        Line::from(vec![
+            prompt,
+            Span::from(before),
+            Span::from(under.to_string()).on_white(),
+            Span::from(after),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(text: &str) -> SearchInput {
+        let mut input = SearchInput::default();
+        text.chars().for_each(|c| input.insert(c));
+        input
+    }
+
+    #[test]
+    fn edits_around_the_cursor() {
+        let mut input = input("owner:kjuulh gitnow");
+        input.move_word_left();
+        input.insert('^');
+        assert_eq!(input.text(), "owner:kjuulh ^gitnow");
+
+        input.move_start();
+        input.delete_forward();
+        assert_eq!(input.text(), "wner:kjuulh ^gitnow");
+
+        input.move_end();
+        assert!(input.delete_word());
+        assert_eq!(input.text(), "wner:kjuulh ");
+        assert!(input.delete_word());
+        assert_eq!(input.text(), "");
+        assert!(!input.delete_backward());
+    }
+
+    #[test]
+    fn kills_to_start_and_end() {
+        let mut input = input("foo bar baz");
+        input.move_start();
+        input.move_word_right();
+        assert!(input.delete_to_end());
+        assert_eq!(input.text(), "foo");
+
+        let mut input = self::input("foo bar baz");
+        input.move_word_left();
+        assert!(input.delete_to_start());
+        assert_eq!(input.text(), "baz");
+        assert!(!input.delete_to_start());
+    }
+}
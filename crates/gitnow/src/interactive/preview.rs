@@ -0,0 +1,175 @@
+use std::{path::Path, process::Command, sync::mpsc};
+
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span, Text},
+};
+
+use crate::{git_provider::Repository, project_metadata::time_ago, query::QueryContext};
+
+use super::Searchable;
+
+/// Number of commits shown for cloned repositories.
+const PREVIEW_COMMITS: usize = 5;
+
+/// Computes previews on a background thread, so slow `git` calls never block
+/// typing. Only the latest request is served; older pending ones are dropped.
+pub struct Previewer<T> {
+    requests: mpsc::Sender<T>,
+    results: mpsc::Receiver<(String, Option<Text<'static>>)>,
+}
+
+impl<T: Searchable> Previewer<T> {
+    pub fn new(context: QueryContext) -> Self {
+        let (requests, request_rx) = mpsc::channel::<T>();
+        let (result_tx, results) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            while let Ok(mut item) = request_rx.recv() {
+                while let Ok(newer) = request_rx.try_recv() {
+                    item = newer;
+                }
+
+                let preview = item.preview(&context);
+                if result_tx.send((item.display_label(), preview)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self { requests, results }
+    }
+
+    pub fn request(&self, item: T) {
+        let _ = self.requests.send(item);
+    }
+
+    /// Returns a finished preview, keyed by the item's label, if any.
+    pub fn try_recv(&self) -> Option<(String, Option<Text<'static>>)> {
+        self.results.try_recv().ok()
+    }
+}
+
+pub fn repository_preview(repository: &Repository, context: &QueryContext) -> Text<'static> {
+    let mut lines = vec![Line::from(repository.to_rel_path().display().to_string()).bold()];
+    if let Some(description) = &repository.description {
+        lines.push(Line::from(description.clone()));
+    }
+
+    lines.push(Line::default());
+    lines.push(field(
+        "default branch",
+        repository.default_branch.as_deref().unwrap_or("unknown"),
+    ));
+    lines.push(field(
+        "last push",
+        &repository
+            .pushed_at
+            .map(time_ago)
+            .unwrap_or_else(|| "unknown".into()),
+    ));
+    if let Some(language) = &repository.language {
+        lines.push(field("language", language));
+    }
+    if !repository.topics.is_empty() {
+        lines.push(field("topics", &repository.topics.join(", ")));
+    }
+    if repository.archived {
+        lines.push(Line::from("archived").yellow());
+    }
+
+    lines.push(Line::default());
+    let path = context.layout.path(repository);
+    let bare = path.join(".bare");
+    if bare.exists() {
+        lines.push(Line::from("worktrees").bold());
+        lines.extend(git_lines(&bare, &["worktree", "list"]));
+    } else if path.exists() {
+        lines.extend(clone_preview(&path));
+    } else {
+        lines.push(Line::from("not cloned").dark_gray());
+    }
+
+    Text::from(lines)
+}
+
+fn clone_preview(path: &Path) -> Vec<Line<'static>> {
+    let Some(branch) = git(path, &["rev-parse", "--abbrev-ref", "HEAD"]) else {
+        return vec![Line::from(format!("cloned at {}", path.display())).dark_gray()];
+    };
+
+    let changed = git(path, &["status", "--porcelain"])
+        .map(|status| status.lines().count())
+        .unwrap_or_default();
+    let state = match changed {
+        0 => Span::from("clean").green(),
+        1 => Span::from("1 changed file").yellow(),
+        n => Span::from(format!("{n} changed files")).yellow(),
+    };
+
+    let mut lines = vec![
+        field("branch", &branch),
+        Line::from(vec![Span::from("state: ").dark_gray(), state]),
+        Line::default(),
+        Line::from("recent commits").bold(),
+    ];
+    lines.extend(git_lines(
+        path,
+        &["log", "--oneline", "-n", &PREVIEW_COMMITS.to_string()],
+    ));
+
+    lines
+}
+
+fn field(name: &str, value: &str) -> Line<'static> {
+    Line::from(vec![
+        Span::from(format!("{name}: ")).dark_gray(),
+        Span::from(value.to_string()),
+    ])
+}
+
+fn git_lines(dir: &Path, args: &[&str]) -> Vec<Line<'static>> {
+    git(dir, args)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect()
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::PathLayout;
+
+    #[test]
+    fn previews_cached_details_of_uncloned_repository() {
+        let repository = Repository {
+            provider: "github.com".into(),
+            owner: "kjuulh".into(),
+            repo_name: "gitnow".into(),
+            description: Some("Git Now".into()),
+            default_branch: Some("main".into()),
+            ..Default::default()
+        };
+        let context = QueryContext {
+            layout: PathLayout::new("/nonexistent"),
+        };
+
+        let text = repository_preview(&repository, &context).to_string();
+
+        assert!(text.contains("github.com/kjuulh/gitnow"));
+        assert!(text.contains("Git Now"));
+        assert!(text.contains("default branch: main"));
+        assert!(text.contains("last push: unknown"));
+        assert!(text.contains("not cloned"));
+    }
+}
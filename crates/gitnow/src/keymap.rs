@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::app::App;
+
+/// Something a key can do in the interactive pickers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PickerAction {
+    Accept,
+    Cancel,
+    /// Move the selection up on screen, regardless of list direction.
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    CursorLeft,
+    CursorRight,
+    CursorStart,
+    CursorEnd,
+    WordLeft,
+    WordRight,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWord,
+    DeleteToStart,
+    DeleteToEnd,
+    TogglePreview,
+    /// Open the action menu for the selected repository.
+    Actions,
+    /// Select or deselect the current item in multi-select pickers.
+    ToggleSelection,
+    /// Fetch the items again, bypassing the cache.
+    Refresh,
+}
+
+/// Emacs-style defaults, used for every action not configured in
+/// `[settings.keybindings]`.
+const DEFAULT_BINDINGS: &[(PickerAction, &[&str])] = &[
+    (PickerAction::Accept, &["enter"]),
+    (PickerAction::Cancel, &["esc", "ctrl-c", "ctrl-g"]),
+    (PickerAction::Up, &["up", "ctrl-p"]),
+    (PickerAction::Down, &["down", "ctrl-n"]),
+    (PickerAction::PageUp, &["pageup", "alt-v"]),
+    (PickerAction::PageDown, &["pagedown", "ctrl-v"]),
+    (PickerAction::CursorLeft, &["left", "ctrl-b"]),
+    (PickerAction::CursorRight, &["right", "ctrl-f"]),
+    (PickerAction::CursorStart, &["home", "ctrl-a"]),
+    (PickerAction::CursorEnd, &["end", "ctrl-e"]),
+    (PickerAction::WordLeft, &["alt-b", "ctrl-left"]),
+    (PickerAction::WordRight, &["alt-f", "ctrl-right"]),
+    (PickerAction::DeleteBackward, &["backspace", "ctrl-h"]),
+    (PickerAction::DeleteForward, &["delete", "ctrl-d"]),
+    (PickerAction::DeleteWord, &["ctrl-w", "alt-backspace"]),
+    (PickerAction::DeleteToStart, &["ctrl-u"]),
+    (PickerAction::DeleteToEnd, &["ctrl-k"]),
+    (PickerAction::TogglePreview, &["ctrl-t"]),
+    (PickerAction::Actions, &["tab", "ctrl-o"]),
+    (PickerAction::ToggleSelection, &["tab"]),
+    (PickerAction::Refresh, &["ctrl-r"]),
+];
+
+/// A key with modifiers, written like `ctrl-w`, `alt-backspace` or `pagedown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let mut modifiers = key.modifiers;
+        // Shift is already part of the character, e.g. `A`.
+        if let KeyCode::Char(_) = key.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl std::str::FromStr for KeyBinding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((modifier, tail)) =
+            rest.split_once('-').filter(|(_, tail)| !tail.is_empty())
+        {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            rest = tail;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => anyhow::bail!("unknown key: {s}"),
+                }
+            }
+        };
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyBinding> for String {
+    fn from(value: KeyBinding) -> Self {
+        let mut parts = Vec::new();
+        if value.modifiers.contains(KeyModifiers::CONTROL) {
+            parts.push("ctrl".to_string());
+        }
+        if value.modifiers.contains(KeyModifiers::ALT) {
+            parts.push("alt".to_string());
+        }
+        if value.modifiers.contains(KeyModifiers::SHIFT) {
+            parts.push("shift".to_string());
+        }
+        parts.push(match value.code {
+            KeyCode::Enter => "enter".into(),
+            KeyCode::Esc => "esc".into(),
+            KeyCode::Tab => "tab".into(),
+            KeyCode::BackTab => "shift-tab".into(),
+            KeyCode::Backspace => "backspace".into(),
+            KeyCode::Delete => "delete".into(),
+            KeyCode::Up => "up".into(),
+            KeyCode::Down => "down".into(),
+            KeyCode::Left => "left".into(),
+            KeyCode::Right => "right".into(),
+            KeyCode::Home => "home".into(),
+            KeyCode::End => "end".into(),
+            KeyCode::PageUp => "pageup".into(),
+            KeyCode::PageDown => "pagedown".into(),
+            KeyCode::Char(' ') => "space".into(),
+            KeyCode::Char(c) => c.to_string(),
+            code => format!("{code:?}").to_lowercase(),
+        });
+
+        parts.join("-")
+    }
+}
+
+/// One key or a list of keys in TOML.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum KeyBindingList {
+    Single(KeyBinding),
+    Multiple(Vec<KeyBinding>),
+}
+
+impl KeyBindingList {
+    pub fn bindings(&self) -> Vec<KeyBinding> {
+        match self {
+            KeyBindingList::Single(binding) => vec![*binding],
+            KeyBindingList::Multiple(bindings) => bindings.clone(),
+        }
+    }
+}
+
+/// Resolves key presses to picker actions.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, PickerAction)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+impl Keymap {
+    /// Configured actions replace their defaults. Configured bindings are
+    /// consulted first, so they win over a default bound to the same key.
+    pub fn new(configured: &BTreeMap<PickerAction, KeyBindingList>) -> Self {
+        let mut bindings: Vec<(KeyBinding, PickerAction)> = configured
+            .iter()
+            .flat_map(|(action, keys)| keys.bindings().into_iter().map(|key| (key, *action)))
+            .collect();
+
+        for (action, keys) in DEFAULT_BINDINGS {
+            if configured.contains_key(action) {
+                continue;
+            }
+            bindings.extend(keys.iter().map(|key| {
+                (
+                    key.parse().expect("default keybindings to be valid"),
+                    *action,
+                )
+            }));
+        }
+
+        Self { bindings }
+    }
+
+    /// Returns the first action bound to `key` among those `supported` by
+    /// the picker.
+    pub fn resolve(&self, key: &KeyEvent, supported: &[PickerAction]) -> Option<PickerAction> {
+        self.bindings
+            .iter()
+            .find(|(binding, action)| binding.matches(key) && supported.contains(action))
+            .map(|(_, action)| *action)
+    }
+}
+
+pub trait KeymapApp {
+    fn keymap(&self) -> Keymap;
+}
+
+impl KeymapApp for &'static App {
+    fn keymap(&self) -> Keymap {
+        match &self.config.settings.keybindings {
+            Some(configured) => Keymap::new(configured),
+            None => Keymap::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        let binding: KeyBinding = "ctrl-w".parse().unwrap();
+        assert!(binding.matches(&key(KeyCode::Char('w'), KeyModifiers::CONTROL)));
+        assert!(!binding.matches(&key(KeyCode::Char('w'), KeyModifiers::NONE)));
+
+        let binding: KeyBinding = "alt-backspace".parse().unwrap();
+        assert!(binding.matches(&key(KeyCode::Backspace, KeyModifiers::ALT)));
+
+        let binding: KeyBinding = "shift-tab".parse().unwrap();
+        assert!(binding.matches(&key(KeyCode::BackTab, KeyModifiers::NONE)));
+
+        let binding: KeyBinding = "-".parse().unwrap();
+        assert!(binding.matches(&key(KeyCode::Char('-'), KeyModifiers::NONE)));
+
+        assert!("ctrl-nope".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn bindings_round_trip_through_strings() {
+        for s in ["ctrl-w", "alt-backspace", "pagedown", "shift-tab", "enter", "space"] {
+            let binding: KeyBinding = s.parse().unwrap();
+            assert_eq!(String::from(binding), s);
+        }
+    }
+
+    #[test]
+    fn configured_bindings_replace_defaults_and_take_precedence() {
+        let configured = BTreeMap::from([(
+            PickerAction::Up,
+            KeyBindingList::Single("ctrl-k".parse().unwrap()),
+        )]);
+        let keymap = Keymap::new(&configured);
+        let all = [PickerAction::Up, PickerAction::DeleteToEnd];
+
+        assert_eq!(
+            keymap.resolve(&key(KeyCode::Char('k'), KeyModifiers::CONTROL), &all),
+            Some(PickerAction::Up)
+        );
+        assert_eq!(
+            keymap.resolve(&key(KeyCode::Char('p'), KeyModifiers::CONTROL), &all),
+            None
+        );
+    }
+
+    #[test]
+    fn resolves_only_supported_actions() {
+        let keymap = Keymap::default();
+        let tab = key(KeyCode::Tab, KeyModifiers::NONE);
+
+        assert_eq!(
+            keymap.resolve(&tab, &[PickerAction::Actions]),
+            Some(PickerAction::Actions)
+        );
+        assert_eq!(
+            keymap.resolve(&tab, &[PickerAction::ToggleSelection]),
+            Some(PickerAction::ToggleSelection)
+        );
+        assert_eq!(keymap.resolve(&tab, &[PickerAction::Accept]), None);
+    }
+}
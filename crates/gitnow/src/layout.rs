@@ -0,0 +1,213 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{app::App, config::Config, git_provider::Repository};
+
+pub const DEFAULT_PATH_TEMPLATE: &str = "{{ provider }}/{{ owner }}/{{ repo }}";
+
+/// Where repositories live on disk: `projects.directory` joined with the
+/// `path_template` of the repository's provider, or the global one.
+#[derive(Debug, Clone, Default)]
+pub struct PathLayout {
+    directory: PathBuf,
+    template: Option<String>,
+    /// Provider domains with their own template.
+    providers: Vec<(String, String)>,
+}
+
+impl PathLayout {
+    /// The default `<provider>/<owner>/<repo>` layout under `directory`.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        let github = config.providers.github.iter().filter_map(|github| {
+            let domain = match &github.url {
+                Some(url) => domain(url)?,
+                None => "github.com".to_string(),
+            };
+            Some((domain, github.path_template.clone()?))
+        });
+        let gitea = config
+            .providers
+            .gitea
+            .iter()
+            .filter_map(|gitea| Some((domain(&gitea.url)?, gitea.path_template.clone()?)));
+
+        Self {
+            template: config.settings.projects.path_template.clone(),
+            providers: github.chain(gitea).collect(),
+            ..Self::new(config.settings.projects.directory.to_path_buf())
+        }
+    }
+
+    /// Replaces every template, e.g. to find clones in a previous layout.
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self.providers.clear();
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The path of `repository` relative to the projects directory.
+    pub fn rel_path(&self, repository: &Repository) -> PathBuf {
+        let template = self
+            .providers
+            .iter()
+            .find(|(domain, _)| *domain == repository.provider)
+            .map(|(_, template)| template)
+            .or(self.template.as_ref());
+
+        let Some(template) = template else {
+            return repository.to_rel_path();
+        };
+
+        match render_path(template, repository) {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::warn!(
+                    "failed to render path template for {}, using the default layout: {e:#}",
+                    repository.to_rel_path().display()
+                );
+                repository.to_rel_path()
+            }
+        }
+    }
+
+    /// Where `repository` is, or would be, cloned.
+    pub fn path(&self, repository: &Repository) -> PathBuf {
+        self.directory.join(self.rel_path(repository))
+    }
+
+    /// How many directories deep under the projects directory the templates
+    /// put clones, at most.
+    pub fn depth(&self) -> usize {
+        let placeholder = Repository {
+            provider: "provider".into(),
+            owner: "owner".into(),
+            repo_name: "repo".into(),
+            ..Default::default()
+        };
+        // The default layout is also the fallback for templates that fail.
+        let templates = self.providers.iter().map(|(_, template)| template.as_str());
+        templates
+            .chain(self.template.as_deref())
+            .chain([DEFAULT_PATH_TEMPLATE])
+            .filter_map(|template| render_path(template, &placeholder).ok())
+            .map(|path| path.components().count())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Renders `template` for `repository`. The result has to be a relative
+/// path that stays inside the projects directory.
+pub fn render_path(template: &str, repository: &Repository) -> anyhow::Result<PathBuf> {
+    let env = minijinja::Environment::new();
+    let rendered = env.render_str(
+        template,
+        minijinja::context! {
+            provider => repository.provider,
+            owner => repository.owner,
+            repo => repository.repo_name,
+        },
+    )?;
+
+    let path = PathBuf::from(rendered.trim());
+    let inside = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if path.as_os_str().is_empty() || !inside {
+        anyhow::bail!("path template rendered to {}", path.display());
+    }
+
+    Ok(path)
+}
+
+fn domain(url: &str) -> Option<String> {
+    url::Url::parse(url).ok()?.domain().map(|d| d.to_string())
+}
+
+pub trait PathLayoutApp {
+    fn layout(&self) -> PathLayout;
+}
+
+impl PathLayoutApp for &'static App {
+    fn layout(&self) -> PathLayout {
+        PathLayout::from_config(&self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(provider: &str) -> Repository {
+        Repository {
+            provider: provider.into(),
+            owner: "kjuulh".into(),
+            repo_name: "gitnow".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn uses_the_provider_template_before_the_global_one() -> anyhow::Result<()> {
+        let config = Config::from_string(
+            r#"
+              [settings]
+              projects = { directory = "/code", path_template = "{{ owner }}/{{ repo }}" }
+
+              [[providers.gitea]]
+              url = "https://git.front.kjuulh.io/api/v1"
+              path_template = "oss/{{ repo }}"
+            "#,
+        )?;
+        let layout = PathLayout::from_config(&config);
+
+        assert_eq!(
+            layout.path(&repository("github.com")),
+            PathBuf::from("/code/kjuulh/gitnow")
+        );
+        assert_eq!(
+            layout.path(&repository("git.front.kjuulh.io")),
+            PathBuf::from("/code/oss/gitnow")
+        );
+        assert_eq!(
+            PathLayout::new("/code").path(&repository("github.com")),
+            PathBuf::from("/code/github.com/kjuulh/gitnow")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn depth_is_the_deepest_template() {
+        let layout = PathLayout::new("/code");
+        assert_eq!(layout.depth(), 3);
+        assert_eq!(layout.clone().with_template("{{ repo }}").depth(), 3);
+        assert_eq!(
+            layout.with_template("src/{{ provider }}/{{ owner }}/{{ repo }}").depth(),
+            4
+        );
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_projects_directory() {
+        let repository = repository("github.com");
+
+        assert!(render_path("../{{ repo }}", &repository).is_err());
+        assert!(render_path("/tmp/{{ repo }}", &repository).is_err());
+        assert!(render_path("{% if false %}x{% endif %}", &repository).is_err());
+        assert_eq!(
+            render_path(DEFAULT_PATH_TEMPLATE, &repository).unwrap(),
+            repository.to_rel_path()
+        );
+    }
+}
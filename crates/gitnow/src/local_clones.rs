@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    app::App, commands::project::get_projects_dir, git_provider::Repository,
+    history::HistoryKind, layout::PathLayoutApp, project_metadata::ProjectMetadata,
+};
+
+/// A checkout on disk: a regular clone, a worktree of a bare clone, or a
+/// repository in a scratch-pad project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalClone {
+    pub kind: HistoryKind,
+    /// `provider/owner/repo` for clones, with `@<worktree>` for worktrees,
+    /// and `<project>/<repo>` for project repositories.
+    pub name: String,
+    pub path: PathBuf,
+    /// `None` for project repositories missing from the project's metadata.
+    pub repository: Option<Repository>,
+}
+
+/// Every checkout of `repositories` under the projects directory, followed
+/// by the repositories in scratch-pad projects, sorted by name within each.
+pub fn find_local_clones(app: &'static App, repositories: &[Repository]) -> Vec<LocalClone> {
+    let layout = app.layout();
+    let mut clones = Vec::new();
+
+    for repository in repositories {
+        let path = layout.path(repository);
+        let name = repository.to_rel_path().display().to_string();
+
+        if path.join(".git").exists() {
+            clones.push(LocalClone {
+                kind: HistoryKind::Repository,
+                name: name.clone(),
+                path: path.clone(),
+                repository: Some(repository.clone()),
+            });
+        }
+        if path.join(".bare").is_dir() {
+            for worktree in checkouts(&path) {
+                clones.push(LocalClone {
+                    kind: HistoryKind::Worktree,
+                    name: format!("{name}@{}", file_name(&worktree)),
+                    path: worktree,
+                    repository: Some(repository.clone()),
+                });
+            }
+        }
+    }
+    clones.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut projects: Vec<_> = subdirectories(&get_projects_dir(app)).collect();
+    projects.sort();
+    for project in projects {
+        let metadata = ProjectMetadata::load(&project);
+        for repo in checkouts(&project) {
+            let repo_name = file_name(&repo);
+            // Prefer the cached repository, which has the provider's fields.
+            let repository = metadata
+                .iter()
+                .flat_map(|metadata| &metadata.repositories)
+                .find(|entry| entry.repo_name == repo_name)
+                .map(|entry| {
+                    repositories
+                        .iter()
+                        .find(|r| {
+                            r.provider == entry.provider
+                                && r.owner == entry.owner
+                                && r.repo_name == entry.repo_name
+                        })
+                        .cloned()
+                        .unwrap_or_else(|| Repository {
+                            provider: entry.provider.clone(),
+                            owner: entry.owner.clone(),
+                            repo_name: entry.repo_name.clone(),
+                            ssh_url: entry.ssh_url.clone(),
+                            ..Default::default()
+                        })
+                });
+
+            clones.push(LocalClone {
+                kind: HistoryKind::Project,
+                name: format!("{}/{repo_name}", file_name(&project)),
+                path: repo,
+                repository,
+            });
+        }
+    }
+
+    clones
+}
+
+/// Every clone under `dir`, at most `depth` directories deep: directories
+/// with a `.git` or `.bare`, whether a provider lists them or not. Hidden
+/// directories, `skip` and anything inside a clone are left out. Reads the
+/// file system synchronously, so async callers use `spawn_blocking`.
+pub fn find_clone_directories(dir: &Path, depth: usize, skip: &Path) -> Vec<PathBuf> {
+    let mut clones = Vec::new();
+    if depth == 0 {
+        return clones;
+    }
+    let mut subdirectories: Vec<_> = subdirectories(dir)
+        .filter(|path| !file_name(path).starts_with('.') && path != skip)
+        .collect();
+    subdirectories.sort();
+
+    for path in subdirectories {
+        if path.join(".git").exists() || path.join(".bare").is_dir() {
+            clones.push(path);
+        } else {
+            clones.extend(find_clone_directories(&path, depth - 1, skip));
+        }
+    }
+
+    clones
+}
+
+/// The subdirectories of `dir` that are checkouts, sorted by name.
+pub fn checkouts(dir: &Path) -> Vec<PathBuf> {
+    let mut checkouts: Vec<_> = subdirectories(dir)
+        .filter(|path| path.join(".git").exists())
+        .collect();
+    checkouts.sort();
+    checkouts
+}
+
+fn subdirectories(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, project_metadata::RepoEntry};
+
+    fn repository(repo_name: &str) -> Repository {
+        Repository {
+            provider: "github.com".into(),
+            owner: "kjuulh".into(),
+            repo_name: repo_name.into(),
+            ssh_url: format!("ssh://git@github.com/kjuulh/{repo_name}.git"),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn finds_clones_worktrees_and_project_repositories() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("gitnow-clones-{}", uuid::Uuid::new_v4()));
+        let code = root.join("code");
+        let projects = root.join("projects");
+        for path in [
+            "code/github.com/kjuulh/gitnow/.git",
+            "code/github.com/kjuulh/bare/.bare",
+            "code/github.com/kjuulh/bare/main",
+            "code/github.com/kjuulh/bare/feature",
+            "code/github.com/kjuulh/missing",
+            "projects/demo/gitnow/.git",
+            "projects/demo/scratch/.git",
+        ] {
+            std::fs::create_dir_all(root.join(path))?;
+        }
+        for worktree in ["main", "feature"] {
+            std::fs::write(
+                code.join("github.com/kjuulh/bare").join(worktree).join(".git"),
+                "gitdir: ../.bare/worktrees",
+            )?;
+        }
+        let repositories = [repository("gitnow"), repository("bare"), repository("missing")];
+        ProjectMetadata::new(
+            "demo".into(),
+            None,
+            vec![RepoEntry::from(&repositories[0])],
+        )
+        .save(&projects.join("demo"))?;
+
+        let app = App::new_static(Config::from_string(&format!(
+            r#"
+              [settings]
+              projects = {{ directory = "{}" }}
+              project = {{ directory = "{}" }}
+            "#,
+            code.display(),
+            projects.display()
+        ))?)
+        .await?;
+
+        let clones = find_local_clones(app, &repositories);
+        let found: Vec<_> = clones
+            .iter()
+            .map(|clone| (clone.kind, clone.name.as_str(), clone.path.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    HistoryKind::Worktree,
+                    "github.com/kjuulh/bare@feature",
+                    code.join("github.com/kjuulh/bare/feature"),
+                ),
+                (
+                    HistoryKind::Worktree,
+                    "github.com/kjuulh/bare@main",
+                    code.join("github.com/kjuulh/bare/main"),
+                ),
+                (
+                    HistoryKind::Repository,
+                    "github.com/kjuulh/gitnow",
+                    code.join("github.com/kjuulh/gitnow"),
+                ),
+                (
+                    HistoryKind::Project,
+                    "demo/gitnow",
+                    projects.join("demo/gitnow"),
+                ),
+                (
+                    HistoryKind::Project,
+                    "demo/scratch",
+                    projects.join("demo/scratch"),
+                ),
+            ]
+        );
+        assert_eq!(clones[3].repository.as_ref(), Some(&repositories[0]));
+        assert_eq!(clones[4].repository, None);
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn finds_clone_directories_within_the_layout_depth() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("gitnow-walk-{}", uuid::Uuid::new_v4()));
+        for path in [
+            "github.com/kjuulh/gitnow/.git",
+            "github.com/kjuulh/gitnow/vendor/nested/.git",
+            "github.com/kjuulh/worktrees/.bare",
+            "github.com/kjuulh/worktrees/main",
+            "github.com/shallow/.git",
+            "github.com/kjuulh/too/deep/.git",
+            "projects/demo/gitnow/.git",
+            ".cache/github.com/kjuulh/.git",
+        ] {
+            std::fs::create_dir_all(root.join(path))?;
+        }
+
+        assert_eq!(
+            find_clone_directories(&root, 3, &root.join("projects")),
+            [
+                root.join("github.com/kjuulh/gitnow"),
+                root.join("github.com/kjuulh/worktrees"),
+                root.join("github.com/shallow"),
+            ]
+        );
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -3,8 +3,11 @@ use std::path::PathBuf;
 use anyhow::Context;
 use clap::{Parser, Subcommand};
 use commands::{
-    clone::CloneCommand, project::ProjectCommand, root::RootCommand, shell::Shell,
-    skill::SkillCommand, update::Update, worktree::WorktreeCommand,
+    clone::CloneCommand, exec::ExecCommand, last::LastCommand,
+    migrate_layout::MigrateLayoutCommand, mirrors::MirrorsCommand, project::ProjectCommand,
+    prune::PruneCommand, recent::RecentCommand, root::{MatchArgs, RootCommand}, shell::Shell,
+    skill::SkillCommand, status::StatusCommand, sync::SyncCommand, update::Update,
+    worktree::WorktreeCommand,
 };
 use config::Config;
 use tracing::level_filters::LevelFilter;
@@ -21,11 +24,20 @@ mod custom_command;
 mod fuzzy_matcher;
 mod git_clone;
 mod git_provider;
+mod git_repo;
+mod history;
 mod interactive;
+mod keymap;
+mod layout;
+mod local_clones;
+mod mirror;
 mod project_metadata;
 mod projects_list;
+mod query;
 mod shell;
 mod template_command;
+mod theme;
+mod updater;
 mod worktree;
 
 #[derive(Parser)]
@@ -67,6 +79,13 @@ struct Command {
 
     #[arg(long = "force-cache-update", default_value = "false")]
     force_cache_update: bool,
+
+    /// Clone with this entry of settings.clone_profiles
+    #[arg(long = "profile")]
+    profile: Option<String>,
+
+    #[command(flatten)]
+    matching: MatchArgs,
 }
 
 #[derive(Subcommand)]
@@ -77,6 +96,22 @@ enum Commands {
     Worktree(WorktreeCommand),
     /// Manage scratch-pad projects with multiple repositories
     Project(ProjectCommand),
+    /// Pick a recently opened repository, worktree or project
+    Recent(RecentCommand),
+    /// Jump back to the previously opened location
+    Last(LastCommand),
+    /// Manage the local mirrors that clones borrow objects from
+    Mirrors(MirrorsCommand),
+    /// Move existing clones to the paths of the current `path_template`
+    MigrateLayout(MigrateLayoutCommand),
+    /// Show uncommitted and unpushed work across local clones
+    Status(StatusCommand),
+    /// Fetch, or with --pull fast-forward, every local clone
+    Sync(SyncCommand),
+    /// Run a command in every local clone matching a search
+    Exec(ExecCommand),
+    /// Delete clones that are gone or archived upstream, or unused
+    Prune(PruneCommand),
     /// Print an LLM-readable reference of all gitnow capabilities
     Skill(SkillCommand),
 }
@@ -139,12 +174,37 @@ async fn main() -> anyhow::Result<()> {
             Commands::Project(mut project) => {
                 project.execute(app, &chooser).await?;
             }
+            Commands::Recent(mut recent) => {
+                recent.execute(app, &chooser).await?;
+            }
+            Commands::Last(mut last) => {
+                last.execute(app, &chooser).await?;
+            }
+            Commands::Mirrors(mut mirrors) => {
+                mirrors.execute(app).await?;
+            }
+            Commands::MigrateLayout(mut migrate) => {
+                migrate.execute(app).await?;
+            }
+            Commands::Status(mut status) => {
+                status.execute(app).await?;
+            }
+            Commands::Sync(mut sync) => {
+                sync.execute(app).await?;
+            }
+            Commands::Exec(mut exec) => {
+                exec.execute(app).await?;
+            }
+            Commands::Prune(mut prune) => {
+                prune.execute(app).await?;
+            }
             Commands::Skill(skill) => {
                 skill.execute().await?;
             }
         },
         None => {
             RootCommand::new(app)
+                .with_clone_profile(cli.profile)
                 .execute(
                     cli.search.as_ref(),
                     !cli.no_cache,
@@ -152,6 +212,7 @@ async fn main() -> anyhow::Result<()> {
                     !no_shell,
                     cli.force_refresh,
                     cli.force_cache_update,
+                    &cli.matching,
                     &chooser,
                 )
                 .await?;
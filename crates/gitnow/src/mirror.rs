@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    app::App,
+    cache::CacheConfig,
+    components::progress_bar::ProgressSender,
+    config::CloneProfile,
+    git_clone::run_clone_command,
+    git_provider::Repository,
+    template_command,
+};
+
+/// A mirror found on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mirror {
+    /// `provider/owner/repo` of the mirrored repository.
+    pub rel_path: String,
+    pub path: PathBuf,
+}
+
+/// One bare `--mirror` clone per repository, under the cache directory by
+/// default. Clones borrow objects from it with `--reference-if-able` and
+/// `--dissociate`, so they never depend on the mirror afterwards.
+pub struct Mirrors {
+    app: &'static App,
+}
+
+impl Mirrors {
+    pub fn new(app: &'static App) -> Self {
+        Self { app }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.app
+            .config
+            .settings
+            .mirrors
+            .as_ref()
+            .and_then(|m| m.enabled)
+            .unwrap_or_default()
+    }
+
+    pub fn directory(&self) -> anyhow::Result<PathBuf> {
+        let configured = self
+            .app
+            .config
+            .settings
+            .mirrors
+            .as_ref()
+            .and_then(|m| m.directory.as_deref());
+
+        match configured {
+            Some(dir) => {
+                let path = PathBuf::from(dir);
+                match path.strip_prefix("~") {
+                    Ok(stripped) => Ok(dirs::home_dir().unwrap_or_default().join(stripped)),
+                    Err(_) => Ok(path),
+                }
+            }
+            None => Ok(self.app.config.get_cache_location()?.join("mirrors")),
+        }
+    }
+
+    pub fn path(&self, repository: &Repository) -> anyhow::Result<PathBuf> {
+        Ok(self.directory()?.join(format!(
+            "{}.git",
+            repository.to_rel_path().display()
+        )))
+    }
+
+    /// Returns the mirror for a clone of `repository` with `profile`,
+    /// creating it first if needed. `None` when mirrors are disabled, the
+    /// profile doesn't use them, or the mirror couldn't be created, in which
+    /// case the clone goes to the remote as usual.
+    pub async fn ensure(
+        &self,
+        repository: &Repository,
+        profile: Option<&CloneProfile>,
+        progress: Option<&ProgressSender>,
+    ) -> Option<PathBuf> {
+        if !self.enabled() || !uses_mirror(profile) {
+            return None;
+        }
+
+        match self.create(repository, progress).await {
+            Ok(path) => Some(path),
+            Err(e) => {
+                tracing::warn!(
+                    "failed to mirror {}, cloning without it: {e:#}",
+                    repository.to_rel_path().display()
+                );
+                None
+            }
+        }
+    }
+
+    async fn create(
+        &self,
+        repository: &Repository,
+        progress: Option<&ProgressSender>,
+    ) -> anyhow::Result<PathBuf> {
+        let path = self.path(repository)?;
+        if path.exists() {
+            return Ok(path);
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let template = self
+            .app
+            .config
+            .settings
+            .mirrors
+            .as_ref()
+            .and_then(|m| m.clone_command.as_deref())
+            .unwrap_or(template_command::DEFAULT_MIRROR_CLONE_COMMAND);
+
+        let path_str = path.display().to_string();
+        let context = HashMap::from([
+            ("ssh_url", repository.ssh_url.as_str()),
+            ("mirror_path", path_str.as_str()),
+        ]);
+
+        tracing::info!("mirroring {} into {}", repository.ssh_url, path.display());
+        run_clone_command(repository, template, context, &path, progress).await?;
+
+        Ok(path)
+    }
+
+    /// Fetches new objects into an existing mirror.
+    pub async fn refresh(&self, mirror: &Mirror) -> anyhow::Result<()> {
+        let path_str = mirror.path.display().to_string();
+        let context = HashMap::from([("mirror_path", path_str.as_str())]);
+
+        let output = template_command::render_and_execute(
+            template_command::DEFAULT_MIRROR_FETCH_COMMAND,
+            context,
+        )
+        .await?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "failed to refresh {}: {}",
+                mirror.rel_path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Every mirror on disk, sorted by path.
+    pub fn list(&self) -> anyhow::Result<Vec<Mirror>> {
+        let directory = self.directory()?;
+        let mut mirrors = Vec::new();
+        collect_mirrors(&directory, &directory, &mut mirrors)?;
+        mirrors.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+
+        Ok(mirrors)
+    }
+}
+
+/// Whether clones with `profile` borrow from a mirror. Shallow and partial
+/// clones don't, since the mirror would download the full history they
+/// avoid.
+fn uses_mirror(profile: Option<&CloneProfile>) -> bool {
+    profile.is_none_or(|profile| profile.depth.is_none() && profile.filter.is_none())
+}
+
+/// Walks `dir` for `<provider>/<owner>/<repo>.git` directories.
+fn collect_mirrors(root: &Path, dir: &Path, mirrors: &mut Vec<Mirror>) -> anyhow::Result<()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let rel = path.strip_prefix(root)?;
+        match rel.to_str().and_then(|rel| rel.strip_suffix(".git")) {
+            Some(rel_path) if rel.components().count() == 3 => mirrors.push(Mirror {
+                rel_path: rel_path.to_string(),
+                path,
+            }),
+            _ if rel.components().count() < 3 => collect_mirrors(root, &path, mirrors)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+pub trait MirrorsApp {
+    fn mirrors(&self) -> Mirrors;
+}
+
+impl MirrorsApp for &'static App {
+    fn mirrors(&self) -> Mirrors {
+        Mirrors::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(depth: Option<u32>, filter: Option<&str>) -> CloneProfile {
+        CloneProfile {
+            name: "monorepo".into(),
+            repositories: Vec::new(),
+            depth,
+            filter: filter.map(Into::into),
+            sparse_paths: vec!["services/api".into()],
+        }
+    }
+
+    #[test]
+    fn shallow_and_partial_clones_skip_the_mirror() {
+        assert!(uses_mirror(None));
+        assert!(uses_mirror(Some(&profile(None, None))));
+        assert!(!uses_mirror(Some(&profile(Some(1), None))));
+        assert!(!uses_mirror(Some(&profile(None, Some("blob:none")))));
+    }
+
+    #[test]
+    fn finds_mirrors_three_levels_down() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("gitnow-mirrors-{}", uuid::Uuid::new_v4()));
+        for path in [
+            "github.com/kjuulh/gitnow.git/objects",
+            "github.com/kjuulh/cuddle.git",
+            "github.com/kjuulh/not-a-mirror",
+            "github.com/stray.git",
+        ] {
+            std::fs::create_dir_all(dir.join(path))?;
+        }
+        std::fs::write(dir.join("github.com/kjuulh/file.git"), "")?;
+
+        let mut mirrors = Vec::new();
+        collect_mirrors(&dir, &dir, &mut mirrors)?;
+        mirrors.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+
+        assert_eq!(
+            mirrors,
+            vec![
+                Mirror {
+                    rel_path: "github.com/kjuulh/cuddle".into(),
+                    path: dir.join("github.com/kjuulh/cuddle.git"),
+                },
+                Mirror {
+                    rel_path: "github.com/kjuulh/gitnow".into(),
+                    path: dir.join("github.com/kjuulh/gitnow.git"),
+                },
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -79,51 +79,56 @@ impl ProjectMetadata {
     }
 
     pub fn created_ago(&self) -> String {
-        let duration = Utc::now().signed_duration_since(self.created_at);
-
-        let days = duration.num_days();
-        if days > 365 {
-            let years = days / 365;
-            return if years == 1 {
-                "1 year ago".into()
-            } else {
-                format!("{years} years ago")
-            };
-        }
-        if days > 30 {
-            let months = days / 30;
-            return if months == 1 {
-                "1 month ago".into()
-            } else {
-                format!("{months} months ago")
-            };
-        }
-        if days > 0 {
-            return if days == 1 {
-                "1 day ago".into()
-            } else {
-                format!("{days} days ago")
-            };
-        }
+        time_ago(self.created_at)
+    }
+}
 
-        let hours = duration.num_hours();
-        if hours > 0 {
-            return if hours == 1 {
-                "1 hour ago".into()
-            } else {
-                format!("{hours} hours ago")
-            };
-        }
+/// Formats `time` relative to now, e.g. `3 days ago`.
+pub fn time_ago(time: DateTime<Utc>) -> String {
+    let duration = Utc::now().signed_duration_since(time);
+
+    let days = duration.num_days();
+    if days > 365 {
+        let years = days / 365;
+        return if years == 1 {
+            "1 year ago".into()
+        } else {
+            format!("{years} years ago")
+        };
+    }
+    if days > 30 {
+        let months = days / 30;
+        return if months == 1 {
+            "1 month ago".into()
+        } else {
+            format!("{months} months ago")
+        };
+    }
+    if days > 0 {
+        return if days == 1 {
+            "1 day ago".into()
+        } else {
+            format!("{days} days ago")
+        };
+    }
 
-        let minutes = duration.num_minutes();
-        if minutes > 0 {
-            return if minutes == 1 {
-                "1 minute ago".into()
-            } else {
-                format!("{minutes} minutes ago")
-            };
-        }
+    let hours = duration.num_hours();
+    if hours > 0 {
+        return if hours == 1 {
+            "1 hour ago".into()
+        } else {
+            format!("{hours} hours ago")
+        };
+    }
 
-        "just now".into()
+    let minutes = duration.num_minutes();
+    if minutes > 0 {
+        return if minutes == 1 {
+            "1 minute ago".into()
+        } else {
+            format!("{minutes} minutes ago")
+        };
     }
+
+    "just now".into()
 }
@@ -17,11 +17,15 @@ impl ProjectsListApp for &'static App {
 }
 
 mod implementation {
+    use tokio::sync::mpsc;
+
     use crate::{
         app::App,
+        config::{GitHub, Gitea},
         git_provider::{
             gitea::GiteaProviderApp, github::GitHubProviderApp, Repository, VecRepositoryExt,
         },
+        interactive::ItemBatch,
     };
 
     pub struct ProjectsList {
@@ -34,100 +38,135 @@ mod implementation {
         }
 
         pub async fn get_projects(&self) -> anyhow::Result<Vec<Repository>> {
-            let mut repositories = Vec::new();
+            let mut batches = self.stream_projects();
 
-            repositories.extend(self.get_gitea_projects().await?);
-            repositories.extend(self.get_github_projects().await?);
+            let mut repositories = Vec::new();
+            while let Some(batch) = batches.recv().await {
+                repositories.extend(batch.items?);
+            }
 
             repositories.collect_unique();
 
             Ok(repositories)
         }
 
-        async fn get_gitea_projects(&self) -> anyhow::Result<Vec<Repository>> {
-            let gitea_provider = self.app.gitea_provider();
+        /// Fetches every configured provider concurrently, sending each
+        /// provider's repositories as soon as it finishes.
+        pub fn stream_projects(&self) -> mpsc::UnboundedReceiver<ItemBatch<Repository>> {
+            let (tx, rx) = mpsc::unbounded_channel();
+
+            for gitea in self.app.config.providers.gitea.iter().cloned() {
+                let (app, tx) = (self.app, tx.clone());
+                tokio::spawn(async move {
+                    let items = get_gitea_projects(app, &gitea).await;
+                    let _ = tx.send(ItemBatch {
+                        source: gitea.url.clone(),
+                        items,
+                    });
+                });
+            }
 
-            let mut repositories = Vec::new();
-            for gitea in self.app.config.providers.gitea.iter() {
-                if let Some(_user) = &gitea.current_user {
-                    let mut repos = gitea_provider
-                        .list_repositories_for_current_user(&gitea.url, gitea.access_token.as_ref())
-                        .await?;
-
-                    repositories.append(&mut repos);
-                }
-
-                for gitea_user in gitea.users.iter() {
-                    let mut repos = gitea_provider
-                        .list_repositories_for_user(
-                            gitea_user.into(),
-                            &gitea.url,
-                            gitea.access_token.as_ref(),
-                        )
-                        .await?;
-
-                    repositories.append(&mut repos);
-                }
-
-                for gitea_org in gitea.organisations.iter() {
-                    let mut repos = gitea_provider
-                        .list_repositories_for_organisation(
-                            gitea_org.into(),
-                            &gitea.url,
-                            gitea.access_token.as_ref(),
-                        )
-                        .await?;
-
-                    repositories.append(&mut repos);
-                }
+            for github in self.app.config.providers.github.iter().cloned() {
+                let (app, tx) = (self.app, tx.clone());
+                tokio::spawn(async move {
+                    let items = get_github_projects(app, &github).await;
+                    let _ = tx.send(ItemBatch {
+                        source: github.url.clone().unwrap_or_else(|| "github.com".into()),
+                        items,
+                    });
+                });
             }
 
-            Ok(repositories)
+            rx
         }
+    }
 
-        async fn get_github_projects(&self) -> anyhow::Result<Vec<Repository>> {
-            let github_provider = self.app.github_provider();
+    async fn get_gitea_projects(
+        app: &'static App,
+        gitea: &Gitea,
+    ) -> anyhow::Result<Vec<Repository>> {
+        let gitea_provider = app.gitea_provider();
 
-            let mut repositories = Vec::new();
-            for github in self.app.config.providers.github.iter() {
-                if let Some(_user) = &github.current_user {
-                    let mut repos = github_provider
-                        .list_repositories_for_current_user(
-                            github.url.as_ref(),
-                            &github.access_token,
-                        )
-                        .await?;
-
-                    repositories.append(&mut repos);
-                }
-
-                for github_user in github.users.iter() {
-                    let mut repos = github_provider
-                        .list_repositories_for_user(
-                            github_user.into(),
-                            github.url.as_ref(),
-                            &github.access_token,
-                        )
-                        .await?;
-
-                    repositories.append(&mut repos);
-                }
-
-                for github_org in github.organisations.iter() {
-                    let mut repos = github_provider
-                        .list_repositories_for_organisation(
-                            github_org.into(),
-                            github.url.as_ref(),
-                            &github.access_token,
-                        )
-                        .await?;
-
-                    repositories.append(&mut repos);
-                }
-            }
+        let mut repositories = Vec::new();
+        if let Some(_user) = &gitea.current_user {
+            let mut repos = gitea_provider
+                .list_repositories_for_current_user(&gitea.url, gitea.access_token.as_ref())
+                .await?;
 
-            Ok(repositories)
+            repositories.append(&mut repos);
+        }
+
+        for gitea_user in gitea.users.iter() {
+            let mut repos = gitea_provider
+                .list_repositories_for_user(
+                    gitea_user.into(),
+                    &gitea.url,
+                    gitea.access_token.as_ref(),
+                )
+                .await?;
+
+            repositories.append(&mut repos);
+        }
+
+        for gitea_org in gitea.organisations.iter() {
+            let mut repos = gitea_provider
+                .list_repositories_for_organisation(
+                    gitea_org.into(),
+                    &gitea.url,
+                    gitea.access_token.as_ref(),
+                )
+                .await?;
+
+            repositories.append(&mut repos);
+        }
+
+        repositories.collect_unique();
+
+        Ok(repositories)
+    }
+
+    async fn get_github_projects(
+        app: &'static App,
+        github: &GitHub,
+    ) -> anyhow::Result<Vec<Repository>> {
+        let github_provider = app.github_provider();
+
+        let mut repositories = Vec::new();
+        if let Some(_user) = &github.current_user {
+            let mut repos = github_provider
+                .list_repositories_for_current_user(github.url.as_ref(), &github.access_token)
+                .await?;
+
+            repositories.append(&mut repos);
         }
+
+        for github_user in github.users.iter() {
+            let mut repos = github_provider
+                .list_repositories_for_user(
+                    github_user.into(),
+                    github.url.as_ref(),
+                    &github.access_token,
+                )
+                .await?;
+
+            repositories.append(&mut repos);
+        }
+
+        for github_org in github.organisations.iter() {
+            let mut repos = github_provider
+                .list_repositories_for_organisation(
+                    github_org.into(),
+                    github.url.as_ref(),
+                    &github.access_token,
+                )
+                .await?;
+
+            repositories.append(&mut repos);
+        }
+
+        repositories.collect_unique();
+
+        Ok(repositories)
     }
 }
 
@@ -1,4 +1,6 @@
-use crate::{app::App, git_provider::Repository};
+use tokio::sync::mpsc;
+
+use crate::{app::App, git_provider::Repository, interactive::ItemBatch};
 
 pub struct ProjectsList {}
 
@@ -48,6 +50,18 @@ impl ProjectsList {
         ]))
     }
 
+    pub fn stream_projects(&self) -> mpsc::UnboundedReceiver<ItemBatch<Repository>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let _ = tx.send(ItemBatch {
+                source: "example".into(),
+                items: ProjectsList {}.get_projects().await,
+            });
+        });
+
+        rx
+    }
+
     fn from_strings(
         &self,
         repos_into: impl IntoIterator<Item = impl Into<Repository>>,
@@ -79,6 +93,7 @@ impl From<&str> for Repository {
             owner: owner.to_string(),
             repo_name: name.to_string(),
             ssh_url: format!("ssh://git@{provider}/{owner}/{name}.git"),
+            ..Default::default()
         }
     }
 }
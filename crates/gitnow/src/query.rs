@@ -0,0 +1,154 @@
+use crate::{git_provider::Repository, layout::PathLayout};
+
+/// A field filter such as `owner:lunarway` or `is:cloned`, applied before the
+/// fuzzy match.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Qualifier {
+    Owner(String),
+    Provider(String),
+    Lang(String),
+    Topic(String),
+    IsCloned,
+    IsArchived,
+}
+
+/// A parsed search string: qualifiers plus the remaining text, which is
+/// matched fuzzily using nucleo's pattern syntax (`'exact`, `^prefix`,
+/// `suffix$`, `!negation`).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Query {
+    pub qualifiers: Vec<Qualifier>,
+    pub text: String,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut qualifiers = Vec::new();
+        let mut text = Vec::new();
+
+        for token in input.split_whitespace() {
+            match Qualifier::parse(token) {
+                Some(qualifier) => qualifiers.push(qualifier),
+                None => text.push(token),
+            }
+        }
+
+        Self {
+            qualifiers,
+            text: text.join(" "),
+        }
+    }
+}
+
+impl Qualifier {
+    /// Parses a `key:value` token. Unknown keys and empty values are not
+    /// qualifiers, so they are matched as ordinary text.
+    fn parse(token: &str) -> Option<Self> {
+        let (key, value) = token.split_once(':')?;
+        if value.is_empty() {
+            return None;
+        }
+
+        let value = value.to_lowercase();
+        let qualifier = match key.to_lowercase().as_str() {
+            "owner" => Qualifier::Owner(value),
+            "provider" => Qualifier::Provider(value),
+            "lang" | "language" => Qualifier::Lang(value),
+            "topic" => Qualifier::Topic(value),
+            "is" => match value.as_str() {
+                "cloned" => Qualifier::IsCloned,
+                "archived" => Qualifier::IsArchived,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some(qualifier)
+    }
+
+    pub fn matches_repository(&self, repository: &Repository, context: &QueryContext) -> bool {
+        match self {
+            Qualifier::Owner(owner) => repository.owner.to_lowercase() == *owner,
+            Qualifier::Provider(provider) => {
+                let actual = repository.provider.to_lowercase();
+                // `provider:github` matches `github.com`
+                actual == *provider || actual.starts_with(&format!("{provider}."))
+            }
+            Qualifier::Lang(lang) => repository
+                .language
+                .as_ref()
+                .is_some_and(|l| l.to_lowercase() == *lang),
+            Qualifier::Topic(topic) => repository
+                .topics
+                .iter()
+                .any(|t| t.to_lowercase() == *topic),
+            Qualifier::IsCloned => context.layout.path(repository).exists(),
+            Qualifier::IsArchived => repository.archived,
+        }
+    }
+}
+
+/// Local state needed to evaluate qualifiers.
+#[derive(Debug, Default, Clone)]
+pub struct QueryContext {
+    pub layout: PathLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository() -> Repository {
+        Repository {
+            provider: "github.com".into(),
+            owner: "lunarway".into(),
+            repo_name: "infra".into(),
+            ssh_url: "ssh://git@github.com/lunarway/infra.git".into(),
+            language: Some("Rust".into()),
+            topics: vec!["infra".into(), "k8s".into()],
+            archived: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_qualifiers_and_keeps_remaining_text() {
+        let query = Query::parse("owner:LunarWay ^inf lang:rust is:cloned 'ra$");
+
+        assert_eq!(
+            query,
+            Query {
+                qualifiers: vec![
+                    Qualifier::Owner("lunarway".into()),
+                    Qualifier::Lang("rust".into()),
+                    Qualifier::IsCloned,
+                ],
+                text: "^inf 'ra$".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_qualifiers_are_treated_as_text() {
+        let query = Query::parse("foo:bar is:weird owner:");
+
+        assert!(query.qualifiers.is_empty());
+        assert_eq!(query.text, "foo:bar is:weird owner:");
+    }
+
+    #[test]
+    fn qualifiers_match_repository_fields() {
+        let repo = repository();
+        let context = QueryContext::default();
+
+        assert!(Qualifier::Owner("lunarway".into()).matches_repository(&repo, &context));
+        assert!(!Qualifier::Owner("kjuulh".into()).matches_repository(&repo, &context));
+        assert!(Qualifier::Provider("github".into()).matches_repository(&repo, &context));
+        assert!(Qualifier::Provider("github.com".into()).matches_repository(&repo, &context));
+        assert!(!Qualifier::Provider("gitea".into()).matches_repository(&repo, &context));
+        assert!(Qualifier::Lang("rust".into()).matches_repository(&repo, &context));
+        assert!(Qualifier::Topic("k8s".into()).matches_repository(&repo, &context));
+        assert!(!Qualifier::Topic("web".into()).matches_repository(&repo, &context));
+        assert!(!Qualifier::IsArchived.matches_repository(&repo, &context));
+    }
+}
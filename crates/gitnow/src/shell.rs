@@ -2,7 +2,7 @@ use std::path::Path;
 
 use anyhow::Context;
 
-use crate::{app::App, git_provider::Repository};
+use crate::{app::App, git_provider::Repository, layout::PathLayoutApp};
 
 pub struct Shell {
     app: &'static App,
@@ -14,13 +14,7 @@ impl Shell {
     }
 
     pub async fn spawn_shell(&self, repository: &Repository) -> anyhow::Result<()> {
-        let project_path = self
-            .app
-            .config
-            .settings
-            .projects
-            .directory
-            .join(repository.to_rel_path());
+        let project_path = self.app.layout().path(repository);
 
         self.spawn_shell_at(&project_path).await
     }
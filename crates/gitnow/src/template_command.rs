@@ -1,10 +1,20 @@
 use std::collections::HashMap;
+use std::process::Stdio;
 
 use anyhow::Context;
+use tokio::io::AsyncReadExt;
 
-pub const DEFAULT_CLONE_COMMAND: &str = "git clone {{ ssh_url }} {{ path }}";
+// `--progress` makes git report progress even though stderr is a pipe.
+// `clone_args` holds the options of the clone profile, if any.
+pub const DEFAULT_CLONE_COMMAND: &str =
+    "git clone --progress {{ clone_args }} {{ ssh_url }} {{ path }}";
 pub const DEFAULT_WORKTREE_CLONE_COMMAND: &str =
-    "git clone --bare {{ ssh_url }} {{ bare_path }}";
+    "git clone --bare --progress {{ clone_args }} {{ ssh_url }} {{ bare_path }}";
+pub const DEFAULT_MIRROR_CLONE_COMMAND: &str =
+    "git clone --mirror --progress {{ ssh_url }} {{ mirror_path }}";
+pub const DEFAULT_MIRROR_FETCH_COMMAND: &str = "git -C {{ mirror_path }} fetch --prune";
+pub const DEFAULT_SPARSE_CHECKOUT_COMMAND: &str =
+    "git -C {{ path }} sparse-checkout set {{ sparse_paths }}";
 pub const DEFAULT_WORKTREE_ADD_COMMAND: &str =
     "git -C {{ bare_path }} worktree add {{ worktree_path }} {{ branch }}";
 pub const DEFAULT_LIST_BRANCHES_COMMAND: &str =
@@ -13,18 +23,88 @@ pub const DEFAULT_LIST_BRANCHES_COMMAND: &str =
 pub async fn render_and_execute(
     template: &str,
     context: HashMap<&str, &str>,
+) -> anyhow::Result<std::process::Output> {
+    render_and_execute_with_progress(template, context, |_| {}).await
+}
+
+/// Like [`render_and_execute`], but streams stderr to `on_line` as it
+/// arrives. Lines end at `\n` or `\r`, since progress output rewrites the
+/// current line with `\r`.
+pub async fn render_and_execute_with_progress(
+    template: &str,
+    context: HashMap<&str, &str>,
+    mut on_line: impl FnMut(&str),
 ) -> anyhow::Result<std::process::Output> {
     let (program, args) = render_command_parts(template, &context)?;
 
     tracing::debug!("executing: {} {}", program, args.join(" "));
 
-    let output = tokio::process::Command::new(&program)
+    let mut child = tokio::process::Command::new(&program)
         .args(&args)
-        .output()
-        .await
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Cancelling a clone should stop it, not leave it running detached.
+        .kill_on_drop(true)
+        .spawn()
         .with_context(|| format!("failed to execute: {} {}", program, args.join(" ")))?;
 
-    Ok(output)
+    let mut stdout = child.stdout.take().expect("stdout to be piped");
+    let mut stderr = child.stderr.take().expect("stderr to be piped");
+
+    let read_stdout = async {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).await.map(|_| buf)
+    };
+    let read_stderr = async {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let mut line_start = 0;
+        loop {
+            let n = stderr.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+
+            while let Some(end) = buf[line_start..]
+                .iter()
+                .position(|b| *b == b'\n' || *b == b'\r')
+            {
+                let line = String::from_utf8_lossy(&buf[line_start..line_start + end]);
+                if !line.trim().is_empty() {
+                    on_line(line.trim_end());
+                }
+                line_start += end + 1;
+            }
+        }
+        let rest = String::from_utf8_lossy(&buf[line_start..]);
+        if !rest.trim().is_empty() {
+            on_line(rest.trim_end());
+        }
+
+        Ok::<_, std::io::Error>(buf)
+    };
+
+    let (stdout, stderr) = tokio::try_join!(read_stdout, read_stderr)
+        .with_context(|| format!("failed to read output of: {program}"))?;
+    let status = child.wait().await?;
+
+    Ok(std::process::Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// The command `template` renders to, quoted for display.
+pub fn render_command_line(
+    template: &str,
+    context: &HashMap<&str, &str>,
+) -> anyhow::Result<String> {
+    let (program, args) = render_command_parts(template, context)?;
+
+    Ok(shell_words::join(std::iter::once(&program).chain(&args)))
 }
 
 fn render_command_parts(
@@ -63,6 +143,32 @@ mod tests {
             args,
             vec![
                 "clone",
+                "--progress",
+                "ssh://git@github.com/owner/repo.git",
+                "/home/user/git/github.com/owner/repo"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_clone_command_with_profile() {
+        let context = HashMap::from([
+            ("ssh_url", "ssh://git@github.com/owner/repo.git"),
+            ("path", "/home/user/git/github.com/owner/repo"),
+            ("clone_args", "--depth 1 --filter blob:none --sparse"),
+        ]);
+
+        let (_, args) = render_command_parts(DEFAULT_CLONE_COMMAND, &context).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "clone",
+                "--progress",
+                "--depth",
+                "1",
+                "--filter",
+                "blob:none",
+                "--sparse",
                 "ssh://git@github.com/owner/repo.git",
                 "/home/user/git/github.com/owner/repo"
             ]
@@ -108,6 +214,7 @@ mod tests {
             vec![
                 "clone",
                 "--bare",
+                "--progress",
                 "ssh://git@github.com/owner/repo.git",
                 "/home/user/git/github.com/owner/repo/.bare"
             ]
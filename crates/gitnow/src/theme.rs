@@ -0,0 +1,96 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, ListDirection},
+};
+
+use crate::{
+    app::App,
+    config::{UiColor, UiHeight, UiListDirection, UiSettings},
+};
+
+/// Resolved `[settings.ui]`, shared by the pickers and spinners.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub prompt: Style,
+    pub highlight: Style,
+    pub highlight_symbol: String,
+    pub matched: Style,
+    pub border: Style,
+    pub spinner: Style,
+    pub direction: ListDirection,
+    /// Inline height; `None` uses the whole screen.
+    pub height: Option<UiHeight>,
+    pub bordered: bool,
+    pub title: Option<String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::from_settings(&UiSettings::default())
+    }
+}
+
+impl Theme {
+    pub fn from_settings(settings: &UiSettings) -> Self {
+        let colors = settings.colors.clone().unwrap_or_default();
+        let color = |c: Option<UiColor>, default: Color| c.map(|c| c.0).unwrap_or(default);
+
+        Self {
+            prompt: Style::default().fg(color(colors.prompt, Color::Blue)),
+            highlight: Style::default()
+                .fg(color(colors.highlight, Color::White))
+                .add_modifier(Modifier::BOLD),
+            highlight_symbol: settings
+                .highlight_symbol
+                .clone()
+                .unwrap_or_else(|| "> ".into()),
+            matched: Style::default()
+                .fg(color(colors.matched, Color::Yellow))
+                .add_modifier(Modifier::BOLD),
+            border: Style::default().fg(color(colors.border, Color::DarkGray)),
+            spinner: colors
+                .spinner
+                .map(|c| Style::default().fg(c.0))
+                .unwrap_or_default(),
+            direction: match settings.list_direction.unwrap_or_default() {
+                UiListDirection::BottomToTop => ListDirection::BottomToTop,
+                UiListDirection::TopToBottom => ListDirection::TopToBottom,
+            },
+            height: settings.height,
+            bordered: settings.border.unwrap_or_default(),
+            title: settings.title.clone(),
+        }
+    }
+
+    /// The outer block for a picker or spinner, if a border or title is set.
+    pub fn block(&self) -> Option<Block<'static>> {
+        if !self.bordered && self.title.is_none() {
+            return None;
+        }
+
+        let mut block = if self.bordered {
+            Block::bordered().border_style(self.border)
+        } else {
+            Block::new()
+        };
+        if let Some(title) = &self.title {
+            block = block.title(Line::from(format!(" {title} ")).style(self.prompt));
+        }
+
+        Some(block)
+    }
+}
+
+pub trait ThemeApp {
+    fn theme(&self) -> Theme;
+}
+
+impl ThemeApp for &'static App {
+    fn theme(&self) -> Theme {
+        match &self.config.settings.ui {
+            Some(ui) => Theme::from_settings(ui),
+            None => Theme::default(),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    app::App,
+    config::{UpdateSettings, UpdateStrategy},
+    git_repo::GitRepo,
+};
+
+const DEFAULT_UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Updates existing clones when they are opened, following
+/// `[settings.update]`.
+pub struct Updater {
+    settings: UpdateSettings,
+}
+
+impl Updater {
+    pub fn new(app: &App) -> Self {
+        Self {
+            settings: app.config.settings.update.clone().unwrap_or_default(),
+        }
+    }
+
+    pub fn strategy(&self) -> UpdateStrategy {
+        self.settings.strategy
+    }
+
+    /// Whether the clone at `path` should be updated now: the strategy isn't
+    /// `none`, and it wasn't fetched within `interval_hours`.
+    pub async fn is_due(&self, path: &Path) -> bool {
+        if self.strategy() == UpdateStrategy::None {
+            return false;
+        }
+        let Some(hours) = self.settings.interval_hours else {
+            return true;
+        };
+
+        let repository = bare_repository(path).unwrap_or_else(|| path.to_path_buf());
+        match GitRepo::new(repository).last_fetch().await {
+            Some(fetched) => SystemTime::now()
+                .duration_since(fetched)
+                .is_ok_and(|age| age >= Duration::from_hours(hours)),
+            None => true,
+        }
+    }
+
+    /// Fetches, and with `pull` fast-forwards the checked out branch if it
+    /// is clean and hasn't diverged. Worktree layouts only fetch their
+    /// `.bare` repository, which also updates its branches that no worktree
+    /// has checked out. Gives up after the configured timeout.
+    pub async fn update(&self, path: &Path) -> anyhow::Result<()> {
+        let bare = bare_repository(path);
+        let update = async {
+            if let Some(bare) = bare {
+                return GitRepo::new(bare).fetch_bare().await;
+            }
+
+            let repo = GitRepo::new(path);
+            repo.fetch().await?;
+
+            if self.strategy() == UpdateStrategy::Pull {
+                repo.fast_forward().await?;
+            }
+
+            anyhow::Ok(())
+        };
+
+        let timeout = self
+            .settings
+            .timeout
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_UPDATE_TIMEOUT);
+        tokio::time::timeout(timeout, update)
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out after {}s", timeout.as_secs()))?
+    }
+
+    /// Prints a one-line warning for uncommitted changes or a branch that
+    /// has diverged from its upstream.
+    pub async fn warn_about_local_changes(&self, path: &Path, label: &str) {
+        // Worktree layouts have no checkout of their own.
+        if bare_repository(path).is_some() {
+            return;
+        }
+        let Ok(status) = GitRepo::new(path).status().await else {
+            return;
+        };
+
+        if status.is_dirty() {
+            let skipped = if self.strategy() == UpdateStrategy::Pull && status.behind > 0 {
+                ", not fast-forwarded"
+            } else {
+                ""
+            };
+            eprintln!(
+                "warning: {label} has {} uncommitted changes{skipped}",
+                status.changes
+            );
+        }
+        if status.has_diverged() {
+            eprintln!(
+                "warning: {} has diverged from {} ({} ahead, {} behind)",
+                status.branch.as_deref().unwrap_or("HEAD"),
+                status.upstream.as_deref().unwrap_or("its upstream"),
+                status.ahead,
+                status.behind
+            );
+        }
+    }
+}
+
+/// The `.bare` repository of a worktree layout at `path`, which has no `.git`
+/// of its own for git to find.
+fn bare_repository(path: &Path) -> Option<PathBuf> {
+    Some(path.join(".bare")).filter(|bare| bare.is_dir())
+}
+
+pub trait UpdaterApp {
+    fn updater(&self) -> Updater;
+}
+
+impl UpdaterApp for &'static App {
+    fn updater(&self) -> Updater {
+        Updater::new(self)
+    }
+}
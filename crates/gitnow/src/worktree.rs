@@ -2,7 +2,15 @@ use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::{app::App, git_provider::Repository, template_command};
+use crate::{
+    app::App,
+    components::progress_bar::ProgressSender,
+    git_clone::{clone_profile, clone_template_vars, run_clone_command},
+    git_provider::Repository,
+    layout::PathLayoutApp,
+    mirror::MirrorsApp,
+    template_command,
+};
 
 pub struct Worktree {
     app: &'static App,
@@ -17,13 +25,7 @@ impl Worktree {
     /// Layout: <project_path>/.bare/ for the bare clone,
     ///         <project_path>/<branch>/ for each worktree.
     pub fn paths(&self, repository: &Repository) -> (PathBuf, PathBuf) {
-        let project_path = self
-            .app
-            .config
-            .settings
-            .projects
-            .directory
-            .join(repository.to_rel_path());
+        let project_path = self.app.layout().path(repository);
         let bare_path = project_path.join(".bare");
         (project_path, bare_path)
     }
@@ -34,26 +36,32 @@ impl Worktree {
         &self,
         repository: &Repository,
         bare_path: &Path,
+        progress: Option<&ProgressSender>,
     ) -> anyhow::Result<()> {
         if bare_path.exists() {
             tracing::info!("bare clone already exists at {}", bare_path.display());
             return Ok(());
         }
 
-        let template = self
-            .app
-            .config
-            .settings
+        let settings = self.app.config.settings.for_repository(repository);
+        let template = settings
             .worktree
             .as_ref()
             .and_then(|w| w.clone_command.as_deref())
             .unwrap_or(template_command::DEFAULT_WORKTREE_CLONE_COMMAND);
 
+        // Sparse paths apply to checkouts, so bare clones only take the
+        // profile's depth and filter.
+        let profile = clone_profile(self.app, repository, None)?;
+        let mirror = self.app.mirrors().ensure(repository, profile, progress).await;
+        let profile_vars = clone_template_vars(profile, mirror.as_deref(), false);
+
         let bare_path_str = bare_path.display().to_string();
-        let context = HashMap::from([
+        let mut context = HashMap::from([
             ("ssh_url", repository.ssh_url.as_str()),
             ("bare_path", bare_path_str.as_str()),
         ]);
+        context.extend(profile_vars.iter().map(|(k, v)| (*k, v.as_str())));
 
         tracing::info!(
             "bare-cloning {} into {}",
@@ -61,21 +69,16 @@ impl Worktree {
             bare_path.display()
         );
 
-        let output = template_command::render_and_execute(template, context).await?;
-
-        if !output.status.success() {
-            let stderr = std::str::from_utf8(&output.stderr).unwrap_or_default();
-            anyhow::bail!("failed to bare-clone: {}", stderr);
-        }
-
-        Ok(())
+        run_clone_command(repository, template, context, bare_path, progress).await
     }
 
-    pub async fn list_branches(&self, bare_path: &Path) -> anyhow::Result<Vec<String>> {
-        let template = self
-            .app
-            .config
-            .settings
+    pub async fn list_branches(
+        &self,
+        repository: &Repository,
+        bare_path: &Path,
+    ) -> anyhow::Result<Vec<String>> {
+        let settings = self.app.config.settings.for_repository(repository);
+        let template = settings
             .worktree
             .as_ref()
             .and_then(|w| w.list_branches_command.as_deref())
@@ -108,14 +111,13 @@ impl Worktree {
 
     pub async fn add_worktree(
         &self,
+        repository: &Repository,
         bare_path: &Path,
         worktree_path: &Path,
         branch: &str,
     ) -> anyhow::Result<()> {
-        let template = self
-            .app
-            .config
-            .settings
+        let settings = self.app.config.settings.for_repository(repository);
+        let template = settings
             .worktree
             .as_ref()
             .and_then(|w| w.add_command.as_deref())